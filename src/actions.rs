@@ -0,0 +1,541 @@
+use std::{error::Error, fmt, io, process::Command, str::FromStr};
+
+use windows::{
+	core::PCWSTR,
+	Win32::{
+		Foundation::{
+			CloseHandle, BOOLEAN, ERROR_NOT_ALL_ASSIGNED, HANDLE, HWND, LPARAM, LUID, WPARAM,
+		},
+		Security::{
+			AdjustTokenPrivileges, LookupPrivilegeValueW, LUID_AND_ATTRIBUTES,
+			SE_PRIVILEGE_ENABLED, SE_SHUTDOWN_NAME, TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES,
+			TOKEN_QUERY,
+		},
+		System::{
+			Diagnostics::Debug::MessageBeep,
+			Power::SetSuspendState,
+			RemoteDesktop::{
+				ProcessIdToSessionId, WTSActive, WTSDisconnectSession, WTSEnumerateSessionsW,
+				WTSFreeMemory, WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION, WTS_SESSION_INFOW,
+			},
+			Shutdown::{
+				ExitWindowsEx, EWX_LOGOFF, EWX_POWEROFF, EWX_REBOOT, EXIT_WINDOWS_FLAGS,
+				SHTDN_REASON_FLAG_PLANNED,
+			},
+			Threading::{GetCurrentProcess, GetCurrentProcessId, OpenProcessToken},
+		},
+		UI::WindowsAndMessaging::{
+			GetDesktopWindow, PostMessageW, EWX_FORCE, MB_OK, SC_MONITORPOWER, WM_SYSCOMMAND,
+		},
+	},
+};
+
+use crate::PolicyScope;
+
+/// Something to do, e.g. when a hotkey is pressed.
+///
+/// Actions parse from and display as their names, e.g. `lock`, `hibernate`, and `force-log-off`, and `exec:` followed
+/// by the command line of [`Exec`](Action::Exec).
+#[derive(Debug, Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Action {
+	/// Lock the workstation, see [`lock_workstation`](crate::lock_workstation).
+	Lock,
+	/// Suspend the system, see [`sleep`].
+	Sleep {
+		/// Whether to hibernate rather than sleep.
+		hibernate: bool,
+		/// Whether to suspend without notifying applications first.
+		force:     bool,
+	},
+	/// Log off the user, see [`log_off`].
+	LogOff {
+		/// Whether to terminate applications without a chance to save unsaved work.
+		force: bool,
+	},
+	/// Shut down the system, see [`shutdown`].
+	Shutdown {
+		/// Whether to terminate applications without a chance to save unsaved work.
+		force: bool,
+	},
+	/// Restart the system, see [`restart`].
+	Restart {
+		/// Whether to terminate applications without a chance to save unsaved work.
+		force: bool,
+	},
+	/// Turn the monitors off, see [`monitor_off`].
+	MonitorOff,
+	/// Start the screensaver, see [`start_screensaver`].
+	Screensaver,
+	/// Disconnect the user's session, see [`disconnect_session`].
+	Disconnect,
+	/// Enable workstation locking if it's disabled, and disable it otherwise, see
+	/// [`set_lock_enabled`](crate::set_lock_enabled).
+	ToggleLockEnabled,
+	/// Start a command without waiting for it, its program followed by its arguments, separated by whitespace except
+	/// within double quotes, e.g. `notepad "C:\notes\to do.txt"`.
+	Exec(String),
+}
+
+impl Action {
+	/// The names of the actions without fields, and of the ones with only flags in their unforced form.
+	const NAMES: [(&'static str, Action); 10] = [
+		("lock", Action::Lock),
+		(
+			"sleep",
+			Action::Sleep {
+				hibernate: false,
+				force:     false,
+			},
+		),
+		(
+			"hibernate",
+			Action::Sleep {
+				hibernate: true,
+				force:     false,
+			},
+		),
+		("log-off", Action::LogOff { force: false }),
+		("shutdown", Action::Shutdown { force: false }),
+		("restart", Action::Restart { force: false }),
+		("monitor-off", Action::MonitorOff),
+		("screensaver", Action::Screensaver),
+		("disconnect", Action::Disconnect),
+		("toggle-lock", Action::ToggleLockEnabled),
+	];
+
+	/// Creates an [`Exec`](Action::Exec) action, [`None`] if the command line has no program.
+	pub fn exec(command: &str) -> Option<Self> {
+		(!split_command(command).is_empty()).then(|| Action::Exec(command.to_owned()))
+	}
+
+	/// The program and arguments an [`Exec`](Action::Exec) action runs, split from its command line by whitespace except
+	/// within double quotes, or [`None`] for other actions.
+	pub fn command_line(&self) -> Option<Vec<String>> {
+		match self {
+			Action::Exec(command) => Some(split_command(command)),
+			_ => None,
+		}
+	}
+
+	/// Performs the action, toggling the current user's locking policy for
+	/// [`ToggleLockEnabled`](Action::ToggleLockEnabled).
+	///
+	/// See [`perform_in`](Action::perform_in).
+	pub fn perform(&self) -> io::Result<()> { self.perform_in(PolicyScope::User) }
+
+	/// Performs the action, toggling the scope's locking policy for [`ToggleLockEnabled`](Action::ToggleLockEnabled).
+	///
+	/// An [`Exec`](Action::Exec) action succeeds once its command starts, and fails with
+	/// [`io::ErrorKind::InvalidInput`] if its command line has no program.
+	pub fn perform_in(&self, scope: PolicyScope) -> io::Result<()> {
+		match *self {
+			Action::Lock => Ok(crate::lock_workstation()?),
+			Action::Sleep { hibernate, force } => sleep(hibernate, force),
+			Action::LogOff { force } => log_off(force),
+			Action::Shutdown { force } => shutdown(force),
+			Action::Restart { force } => restart(force),
+			Action::MonitorOff => monitor_off(),
+			Action::Screensaver => start_screensaver(),
+			Action::Disconnect => disconnect_session(),
+			Action::ToggleLockEnabled => {
+				crate::set_lock_enabled_in(scope, !crate::get_lock_enabled_in(scope)?)
+			}
+			Action::Exec(_) => {
+				let command = self.command_line().unwrap_or_default();
+				let (program, args) = command.split_first().ok_or_else(|| {
+					io::Error::new(io::ErrorKind::InvalidInput, ParseActionError::EmptyCommand)
+				})?;
+				Command::new(program).args(args).spawn().map(drop)
+			}
+		}
+	}
+
+	/// The unforced form of the action, and whether it's forced.
+	fn unforced(&self) -> (Self, bool) {
+		match *self {
+			Action::Sleep { hibernate, force } => (
+				Action::Sleep {
+					hibernate,
+					force: false,
+				},
+				force,
+			),
+			Action::LogOff { force } => (Action::LogOff { force: false }, force),
+			Action::Shutdown { force } => (Action::Shutdown { force: false }, force),
+			Action::Restart { force } => (Action::Restart { force: false }, force),
+			ref action => (action.clone(), false),
+		}
+	}
+
+	/// The forced form of the action, [`None`] if it has none.
+	fn forced(self) -> Option<Self> {
+		match self {
+			Action::Sleep { hibernate, .. } => Some(Action::Sleep {
+				hibernate,
+				force: true,
+			}),
+			Action::LogOff { .. } => Some(Action::LogOff { force: true }),
+			Action::Shutdown { .. } => Some(Action::Shutdown { force: true }),
+			Action::Restart { .. } => Some(Action::Restart { force: true }),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for Action {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Action::Exec(command) = self {
+			return write!(f, "exec:{command}");
+		}
+		let (unforced, force) = self.unforced();
+		let (name, _) = Action::NAMES
+			.into_iter()
+			.find(|(_, action)| *action == unforced)
+			.expect("every action is named");
+		if force {
+			f.write_str("force-")?;
+		}
+		f.write_str(name)
+	}
+}
+
+impl FromStr for Action {
+	type Err = ParseActionError;
+
+	/// Parses the action's name case-insensitively, and the command line of `exec:` as is.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		if s.get(..5)
+			.is_some_and(|prefix| prefix.eq_ignore_ascii_case("exec:"))
+		{
+			return Action::exec(&s[5..]).ok_or(ParseActionError::EmptyCommand);
+		}
+		let (name, force) = match s.get(..6) {
+			Some(prefix) if prefix.eq_ignore_ascii_case("force-") => (&s[6..], true),
+			_ => (s, false),
+		};
+		Action::NAMES
+			.into_iter()
+			.find_map(|(candidate, action)| candidate.eq_ignore_ascii_case(name).then_some(action))
+			.and_then(|action| if force { action.forced() } else { Some(action) })
+			.ok_or_else(|| ParseActionError::UnknownAction(s.to_owned()))
+	}
+}
+
+/// The error of parsing an [`Action`].
+#[derive(Debug, Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
+pub enum ParseActionError {
+	/// No action has the name.
+	UnknownAction(String),
+	/// The command line of `exec:` has no program.
+	EmptyCommand,
+}
+
+impl fmt::Display for ParseActionError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ParseActionError::UnknownAction(name) => write!(f, "unknown action \"{name}\""),
+			ParseActionError::EmptyCommand => f.write_str("the command to execute is empty"),
+		}
+	}
+}
+
+impl Error for ParseActionError {}
+
+/// Splits the command line into arguments by whitespace, except within double quotes.
+fn split_command(command: &str) -> Vec<String> {
+	let mut args = Vec::new();
+	let mut arg = String::new();
+	let mut quoted = false;
+	let mut started = false;
+	for c in command.chars() {
+		match c {
+			'"' => {
+				quoted = !quoted;
+				started = true;
+			}
+			c if c.is_whitespace() && !quoted => {
+				if std::mem::take(&mut started) {
+					args.push(std::mem::take(&mut arg));
+				}
+			}
+			c => {
+				arg.push(c);
+				started = true;
+			}
+		}
+	}
+	if started {
+		args.push(arg);
+	}
+	args
+}
+
+/// Suspends the system, either to sleep or to hibernation.
+///
+/// If `force` is set the system suspends immediately, otherwise applications are notified first.
+///
+/// Corresponds to [SetSuspendState](https://learn.microsoft.com/en-us/windows/win32/api/powrprof/nf-powrprof-setsuspendstate).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub fn sleep(hibernate: bool, force: bool) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	acquire_shutdown_privilege()?;
+	let result = unsafe {
+		SetSuspendState(
+			BOOLEAN::from(hibernate),
+			BOOLEAN::from(force),
+			BOOLEAN::from(false),
+		)
+	}
+	.as_bool();
+	if result {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// Logs off the user.
+///
+/// If `force` is set applications are terminated without a chance to save unsaved work.
+///
+/// Corresponds to [ExitWindowsEx](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-exitwindowsex) with `EWX_LOGOFF`.
+pub fn log_off(force: bool) -> io::Result<()> { exit_windows(EWX_LOGOFF, force) }
+
+/// Disconnects the user's session, leaving it running so the user can reconnect to it.
+///
+/// A remote session's connection closes, and the console session goes to the sign-in screen, where another user can
+/// sign in, as with switching users.
+///
+/// Corresponds to [WTSDisconnectSession](https://learn.microsoft.com/en-us/windows/win32/api/wtsapi32/nf-wtsapi32-wtsdisconnectsession).
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn disconnect_session() -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let result =
+		unsafe { WTSDisconnectSession(WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION, true) }
+			.as_bool();
+	if result {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// Disconnects every active session of the machine other than the calling one, e.g. to secure a multi-session server,
+/// returning each session's identifier with its result.
+///
+/// It disconnects rather than locks, as with [`disconnect_session`]: their users must sign in again to reconnect, and
+/// their programs keep running. Disconnecting other sessions requires running elevated, without which their results
+/// are access denied errors. The calling session is left alone, so lock it with [`lock_workstation`](crate::lock_workstation).
+///
+/// Corresponds to [WTSEnumerateSessionsW](https://learn.microsoft.com/en-us/windows/win32/api/wtsapi32/nf-wtsapi32-wtsenumeratesessionsw)
+/// and [WTSDisconnectSession](https://learn.microsoft.com/en-us/windows/win32/api/wtsapi32/nf-wtsapi32-wtsdisconnectsession).
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn disconnect_all_sessions() -> io::Result<Vec<(u32, io::Result<()>)>> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let mut own = 0;
+	if !unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut own) }.as_bool() {
+		return Err(io::Error::last_os_error());
+	}
+	let mut sessions: *mut WTS_SESSION_INFOW = std::ptr::null_mut();
+	let mut count = 0;
+	if !unsafe { WTSEnumerateSessionsW(WTS_CURRENT_SERVER_HANDLE, 0, 1, &mut sessions, &mut count) }
+		.as_bool()
+	{
+		return Err(io::Error::last_os_error());
+	}
+	let active = unsafe { std::slice::from_raw_parts(sessions, count as usize) }
+		.iter()
+		.filter(|session| session.State == WTSActive && session.SessionId != own)
+		.map(|session| session.SessionId)
+		.collect::<Vec<_>>();
+	unsafe { WTSFreeMemory(sessions.cast()) };
+	Ok(active
+		.into_iter()
+		.map(|id| {
+			let result = if unsafe { WTSDisconnectSession(WTS_CURRENT_SERVER_HANDLE, id, false) }
+				.as_bool()
+			{
+				Ok(())
+			} else {
+				Err(io::Error::last_os_error())
+			};
+			(id, result)
+		})
+		.collect())
+}
+
+/// Shuts down the system and turns off the power.
+///
+/// If `force` is set applications are terminated without a chance to save unsaved work.
+///
+/// Corresponds to [ExitWindowsEx](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-exitwindowsex) with `EWX_POWEROFF`.
+pub fn shutdown(force: bool) -> io::Result<()> {
+	acquire_shutdown_privilege()?;
+	exit_windows(EWX_POWEROFF, force)
+}
+
+/// Restarts the system.
+///
+/// If `force` is set applications are terminated without a chance to save unsaved work.
+///
+/// Corresponds to [ExitWindowsEx](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-exitwindowsex) with `EWX_REBOOT`.
+pub fn restart(force: bool) -> io::Result<()> {
+	acquire_shutdown_privilege()?;
+	exit_windows(EWX_REBOOT, force)
+}
+
+/// Turns off the monitors.
+///
+/// Any input, like moving the mouse, turns them back on, which is the expected behavior.
+///
+/// Corresponds to broadcasting [WM_SYSCOMMAND](https://learn.microsoft.com/en-us/windows/win32/menurc/wm-syscommand) with `SC_MONITORPOWER`.
+pub fn monitor_off() -> io::Result<()> { set_monitor_power(MONITOR_POWER_OFF) }
+
+/// Turns on the monitors.
+///
+/// Recent Windows versions may ignore this, in which case input turns the monitors on instead.
+///
+/// Corresponds to broadcasting [WM_SYSCOMMAND](https://learn.microsoft.com/en-us/windows/win32/menurc/wm-syscommand) with `SC_MONITORPOWER`.
+pub fn monitor_on() -> io::Result<()> { set_monitor_power(MONITOR_POWER_ON) }
+
+/// Posts a message to all top-level windows.
+const HWND_BROADCAST: HWND = HWND(0xFFFF);
+/// The `SC_MONITORPOWER` state that turns the monitors on.
+const MONITOR_POWER_ON: isize = -1;
+/// The `SC_MONITORPOWER` state that turns the monitors off.
+const MONITOR_POWER_OFF: isize = 2;
+
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+fn set_monitor_power(state: isize) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let result = unsafe {
+		PostMessageW(
+			HWND_BROADCAST,
+			WM_SYSCOMMAND,
+			WPARAM(SC_MONITORPOWER as _),
+			LPARAM(state),
+		)
+	}
+	.as_bool();
+	if result {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// Starts the screensaver.
+///
+/// Whether the screensaver locks the workstation depends on its "On resume, display logon screen" setting.
+///
+/// Corresponds to posting [WM_SYSCOMMAND](https://learn.microsoft.com/en-us/windows/win32/menurc/wm-syscommand) with `SC_SCREENSAVE`
+/// to the desktop window.
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn start_screensaver() -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let result = unsafe {
+		PostMessageW(
+			GetDesktopWindow(),
+			WM_SYSCOMMAND,
+			WPARAM(SC_SCREENSAVE as _),
+			LPARAM(0),
+		)
+	}
+	.as_bool();
+	if result {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// The [WM_SYSCOMMAND](https://learn.microsoft.com/en-us/windows/win32/menurc/wm-syscommand) command that starts the screensaver.
+const SC_SCREENSAVE: u32 = 0xF140;
+
+/// Plays the default system sound, asynchronously.
+///
+/// Corresponds to [MessageBeep](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-messagebeep) with `MB_OK`.
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn beep() -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	if unsafe { MessageBeep(MB_OK) }.as_bool() {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+fn exit_windows(flags: EXIT_WINDOWS_FLAGS, force: bool) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let flags = if force {
+		EXIT_WINDOWS_FLAGS(flags.0 | EWX_FORCE)
+	} else {
+		flags
+	};
+	let result = unsafe { ExitWindowsEx(flags, SHTDN_REASON_FLAG_PLANNED) }.as_bool();
+	if result {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// Enables the shutdown privilege for the process, which shutting down, restarting, and suspending require.
+#[cfg_attr(not(windows), allow(unreachable_code))]
+fn acquire_shutdown_privilege() -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let mut token = HANDLE::default();
+	let opened = unsafe {
+		OpenProcessToken(
+			GetCurrentProcess(),
+			TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY,
+			&mut token,
+		)
+	}
+	.as_bool();
+	if !opened {
+		return Err(io::Error::last_os_error());
+	}
+	let mut privileges = TOKEN_PRIVILEGES {
+		PrivilegeCount: 1,
+		Privileges:     [LUID_AND_ATTRIBUTES {
+			Luid:       LUID::default(),
+			Attributes: SE_PRIVILEGE_ENABLED,
+		}],
+	};
+	let result = (|| {
+		let found = unsafe {
+			LookupPrivilegeValueW(
+				PCWSTR::null(),
+				SE_SHUTDOWN_NAME,
+				&mut privileges.Privileges[0].Luid,
+			)
+		}
+		.as_bool();
+		if !found {
+			return Err(io::Error::last_os_error());
+		}
+		let adjusted =
+			unsafe { AdjustTokenPrivileges(token, false, Some(&privileges), 0, None, None) }
+				.as_bool();
+		// succeeding doesn't mean the privilege was enabled, which is reported through the last error
+		let error = io::Error::last_os_error();
+		if !adjusted || error.raw_os_error() == Some(ERROR_NOT_ALL_ASSIGNED.0 as _) {
+			Err(error)
+		} else {
+			Ok(())
+		}
+	})();
+	unsafe { CloseHandle(token) };
+	result
+}
@@ -0,0 +1,269 @@
+use std::io;
+
+use windows::Win32::{
+	Foundation::HWND,
+	UI::{
+		Input::KeyboardAndMouse::{RegisterHotKey, UnregisterHotKey},
+		WindowsAndMessaging::{GetMessageW, MSG, WM_HOTKEY},
+	},
+};
+
+use crate::{
+	hook::{EXTENDED_KEY_FLAG, HOOK_MESSAGE_FLAG},
+	Hotkey, HotkeyId, Key, Modifiers, PolicyScope, MESSAGE_FILTER_MAX, MESSAGE_FILTER_MIN,
+	WM_HOTKEY_RELEASED,
+};
+
+/// A Windows message, as taken from the message queue.
+#[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Message {
+	/// The message identifier, e.g. `WM_HOTKEY`.
+	pub message: u32,
+	/// The message's `wParam`.
+	pub wparam:  usize,
+	/// The message's `lParam`.
+	pub lparam:  isize,
+}
+
+impl From<&MSG> for Message {
+	fn from(message: &MSG) -> Self {
+		Self {
+			message: message.message,
+			wparam:  message.wParam.0,
+			lparam:  message.lParam.0,
+		}
+	}
+}
+
+impl Message {
+	/// The hotkey that was pressed, if it's a `WM_HOTKEY` message, decoded from its `lParam`.
+	///
+	/// It's the combination that was actually pressed, which is the registered one without
+	/// [`NoRepeat`](Modifiers::NoRepeat).
+	///
+	/// Corresponds to [WM_HOTKEY](https://learn.microsoft.com/en-us/windows/win32/inputdev/wm-hotkey).
+	pub fn pressed_hotkey(&self) -> Option<Hotkey> {
+		if self.message != WM_HOTKEY {
+			return None;
+		}
+		let lparam = self.lparam as usize;
+		Some(Hotkey {
+			modifiers: Modifiers::from_bits_truncate((lparam & 0xFFFF) as u32),
+			key_code:  Key((lparam >> 16 & 0xFFFF) as u32),
+		})
+	}
+
+	/// Whether the key of a hotkey press or release was an extended key, if the hook posted the message (see
+	/// [`Hotkey::register_hook`]).
+	///
+	/// It's the key event's actual extended flag, so unlike [`Key::is_extended`] it tells e.g. the numeric keypad's
+	/// <kbd>Enter</kbd> from the main one. `RegisterHotKey` doesn't report the flag, so its messages and other
+	/// messages give [`None`].
+	///
+	/// Corresponds to [KBDLLHOOKSTRUCT](https://learn.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-kbdllhookstruct)'s `LLKHF_EXTENDED`.
+	pub fn extended_key(&self) -> Option<bool> {
+		let hotkey_message = self.message == WM_HOTKEY || self.message == WM_HOTKEY_RELEASED;
+		(hotkey_message && self.lparam & HOOK_MESSAGE_FLAG != 0)
+			.then_some(self.lparam & EXTENDED_KEY_FLAG != 0)
+	}
+}
+
+/// The Windows procedures the crate's logic is built on, so it can run against something other than the system.
+///
+/// [`SystemApi`] is the real implementation, which the crate's functions use unless given another (e.g.
+/// [`await_event_with`](crate::await_event_with) and [`HotkeyManager::with_api`](crate::HotkeyManager::with_api)).
+pub trait WinApi {
+	/// Registers the hotkey under the identifier (see [`Hotkey::register_with_id`]).
+	fn register_hotkey(&self, id: HotkeyId, hotkey: Hotkey) -> io::Result<()>;
+
+	/// Unregisters the hotkey registered under the identifier (see [`Hotkey::unregister_with_id`]).
+	fn unregister_hotkey(&self, id: HotkeyId) -> io::Result<()>;
+
+	/// Blocks until the next message the crate handles (see [`await_event`](crate::await_event)).
+	///
+	/// A quit signal is returned as a `WM_QUIT` message.
+	fn get_message(&self) -> io::Result<Message>;
+
+	/// Locks the workstation (see [`lock_workstation`](crate::lock_workstation)).
+	fn lock_workstation(&self) -> io::Result<()>;
+
+	/// Reads the raw `DisableLockWorkstation` registry value, [`None`] if absent.
+	fn reg_get_value(&self) -> io::Result<Option<u32>>;
+
+	/// Writes the raw `DisableLockWorkstation` registry value, deleting it for [`None`].
+	fn reg_set_value(&self, value: Option<u32>) -> io::Result<()>;
+}
+
+impl<T: WinApi + ?Sized> WinApi for &T {
+	fn register_hotkey(&self, id: HotkeyId, hotkey: Hotkey) -> io::Result<()> {
+		(**self).register_hotkey(id, hotkey)
+	}
+
+	fn unregister_hotkey(&self, id: HotkeyId) -> io::Result<()> { (**self).unregister_hotkey(id) }
+
+	fn get_message(&self) -> io::Result<Message> { (**self).get_message() }
+
+	fn lock_workstation(&self) -> io::Result<()> { (**self).lock_workstation() }
+
+	fn reg_get_value(&self) -> io::Result<Option<u32>> { (**self).reg_get_value() }
+
+	fn reg_set_value(&self, value: Option<u32>) -> io::Result<()> { (**self).reg_set_value(value) }
+}
+
+/// The [`WinApi`] of the actual system.
+#[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct SystemApi;
+
+impl WinApi for SystemApi {
+	/// Corresponds to [RegisterHotKey](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey).
+	#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+	fn register_hotkey(&self, id: HotkeyId, hotkey: Hotkey) -> io::Result<()> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let success = unsafe {
+			RegisterHotKey(
+				HWND::default(),
+				id.0,
+				hotkey.modifiers.into(),
+				hotkey.key_code.0,
+			)
+			.as_bool()
+		};
+		if success {
+			Ok(())
+		} else {
+			Err(io::Error::last_os_error())
+		}
+	}
+
+	/// Corresponds to [UnregisterHotKey](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey).
+	#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+	fn unregister_hotkey(&self, id: HotkeyId) -> io::Result<()> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let success = unsafe { UnregisterHotKey(HWND::default(), id.0).as_bool() };
+		if success {
+			Ok(())
+		} else {
+			Err(io::Error::last_os_error())
+		}
+	}
+
+	/// Corresponds to [GetMessageW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew).
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	fn get_message(&self) -> io::Result<Message> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let mut message = Default::default();
+		let message_result = unsafe {
+			GetMessageW(
+				&mut message,
+				HWND::default(),
+				MESSAGE_FILTER_MIN,
+				MESSAGE_FILTER_MAX,
+			)
+		};
+		match message_result.0 {
+			-1 => Err(io::Error::last_os_error()),
+			_ => Ok(Message::from(&message)),
+		}
+	}
+
+	fn lock_workstation(&self) -> io::Result<()> { Ok(crate::lock_workstation()?) }
+
+	fn reg_get_value(&self) -> io::Result<Option<u32>> { crate::lock_policy(PolicyScope::User) }
+
+	fn reg_set_value(&self, value: Option<u32>) -> io::Result<()> {
+		crate::set_lock_policy(PolicyScope::User, value)
+	}
+}
+
+#[cfg(feature = "mock")]
+pub use mock::MockApi;
+
+#[cfg(feature = "mock")]
+mod mock {
+	use std::{cell::RefCell, collections::VecDeque, io};
+
+	use windows::Win32::{
+		Foundation::ERROR_HOTKEY_ALREADY_REGISTERED, UI::WindowsAndMessaging::WM_QUIT,
+	};
+
+	use super::{Message, WinApi};
+	use crate::{Hotkey, HotkeyId};
+
+	/// A [`WinApi`] that records the calls instead of making them, for testing.
+	///
+	/// Messages are served from a queue filled with [`push_message`](MockApi::push_message), and a `WM_QUIT` once it's empty.
+	#[derive(Debug, Default)]
+	pub struct MockApi {
+		hotkeys:  RefCell<Vec<(HotkeyId, Hotkey)>>,
+		messages: RefCell<VecDeque<Message>>,
+		locks:    RefCell<usize>,
+		registry: RefCell<Option<u32>>,
+	}
+
+	impl MockApi {
+		/// Creates a mock with no hotkeys, messages, or registry value.
+		pub fn new() -> Self { Self::default() }
+
+		/// Queues a message for [`get_message`](WinApi::get_message).
+		pub fn push_message(&self, message: Message) {
+			self.messages.borrow_mut().push_back(message);
+		}
+
+		/// The registered hotkeys and their identifiers.
+		pub fn hotkeys(&self) -> Vec<(HotkeyId, Hotkey)> { self.hotkeys.borrow().clone() }
+
+		/// How many times the workstation was locked.
+		pub fn locks(&self) -> usize { *self.locks.borrow() }
+
+		/// The registry value.
+		pub fn registry(&self) -> Option<u32> { *self.registry.borrow() }
+	}
+
+	impl WinApi for MockApi {
+		fn register_hotkey(&self, id: HotkeyId, hotkey: Hotkey) -> io::Result<()> {
+			let mut hotkeys = self.hotkeys.borrow_mut();
+			if hotkeys
+				.iter()
+				.any(|&(other_id, other)| other_id == id || other == hotkey)
+			{
+				return Err(io::Error::from_raw_os_error(
+					ERROR_HOTKEY_ALREADY_REGISTERED.0 as _,
+				));
+			}
+			hotkeys.push((id, hotkey));
+			Ok(())
+		}
+
+		fn unregister_hotkey(&self, id: HotkeyId) -> io::Result<()> {
+			let mut hotkeys = self.hotkeys.borrow_mut();
+			let index = hotkeys
+				.iter()
+				.position(|&(hotkey_id, _)| hotkey_id == id)
+				.ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+			hotkeys.remove(index);
+			Ok(())
+		}
+
+		fn get_message(&self) -> io::Result<Message> {
+			Ok(self.messages.borrow_mut().pop_front().unwrap_or(Message {
+				message: WM_QUIT,
+				..Default::default()
+			}))
+		}
+
+		fn lock_workstation(&self) -> io::Result<()> {
+			*self.locks.borrow_mut() += 1;
+			Ok(())
+		}
+
+		fn reg_get_value(&self) -> io::Result<Option<u32>> { Ok(self.registry()) }
+
+		fn reg_set_value(&self, value: Option<u32>) -> io::Result<()> {
+			*self.registry.borrow_mut() = value;
+			Ok(())
+		}
+	}
+}
@@ -0,0 +1,63 @@
+use std::{
+	fs::{File, OpenOptions},
+	io::{self, Write},
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A log file of timestamped events, e.g. a record of when the workstation was locked.
+///
+/// Each entry is a line of an ISO 8601 UTC timestamp followed by the event, e.g. `2023-06-14T09:26:53Z locked`.
+#[derive(Debug)]
+pub struct AuditLogger {
+	file: File,
+}
+
+impl AuditLogger {
+	/// Opens the log file for appending, creating it if it doesn't exist.
+	pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		Ok(Self { file })
+	}
+
+	/// Appends an entry of the event at the current time, and flushes it to the file.
+	pub fn log(&mut self, event: &str) -> io::Result<()> { self.log_at(SystemTime::now(), event) }
+
+	/// Appends an entry of the event at the given time, and flushes it to the file.
+	///
+	/// Times before the Unix epoch are written as the epoch.
+	pub fn log_at(&mut self, time: SystemTime, event: &str) -> io::Result<()> {
+		writeln!(self.file, "{} {event}", timestamp(time))?;
+		self.file.flush()
+	}
+}
+
+/// Formats the time as an ISO 8601 UTC timestamp, to the second.
+fn timestamp(time: SystemTime) -> String {
+	let seconds = time
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+	let (days, second_of_day) = (seconds / 86400, seconds % 86400);
+	// the civil date of the days since the epoch, per Howard Hinnant's `civil_from_days`
+	let days = days + 719468;
+	let era = days / 146097;
+	let day_of_era = days % 146097;
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+	let month = if month_index < 10 {
+		month_index + 3
+	} else {
+		month_index - 9
+	};
+	let year = year_of_era + era * 400 + (month <= 2) as u64;
+	format!(
+		"{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+		second_of_day / 3600,
+		second_of_day / 60 % 60,
+		second_of_day % 60
+	)
+}
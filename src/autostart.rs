@@ -0,0 +1,66 @@
+use std::{io, process::Command};
+
+use windows::{core::PCWSTR, w, Win32::System::Registry::HKEY_CURRENT_USER};
+
+use crate::registry;
+
+/// The registry key of the commands Windows runs at the user's login, under `HKEY_CURRENT_USER`.
+const RUN_KEY: PCWSTR = w!(r"Software\Microsoft\Windows\CurrentVersion\Run");
+
+/// Has Windows run the command when the user logs in, under the given name, replacing the name's previous command.
+///
+/// The command is a command line, so paths with spaces must be quoted. It runs without elevation; programs that need it
+/// should be scheduled with Task Scheduler instead. `HKEY_LOCAL_MACHINE`'s equivalent key, which runs for all users,
+/// isn't supported.
+///
+/// Corresponds to [RegSetKeyValueW](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regsetkeyvaluew)
+/// on the [Run key](https://learn.microsoft.com/en-us/windows/win32/setupapi/run-and-runonce-registry-keys).
+pub fn enable_autostart(name: &str, command: &str) -> io::Result<()> {
+	let name = registry::wide(name);
+	registry::set_string(HKEY_CURRENT_USER, RUN_KEY, PCWSTR(name.as_ptr()), command)
+}
+
+/// Has Task Scheduler run the command elevated when the user logs in, as a task of the given name, replacing the name's
+/// previous task.
+///
+/// Unlike [`enable_autostart`], it suits programs that need elevation, which creating the task itself requires too.
+/// The task starts at any user's login and runs as the user that created it, in that user's session.
+/// Windows services aren't an alternative, since they run in session 0, isolated from the user's desktop.
+///
+/// Corresponds to [schtasks create](https://learn.microsoft.com/en-us/windows-server/administration/windows-commands/schtasks-create)
+/// with `/SC ONLOGON /RL HIGHEST`.
+pub fn enable_task_autostart(name: &str, command: &str) -> io::Result<()> {
+	schtasks(&[
+		"/Create", "/F", "/TN", name, "/TR", command, "/SC", "ONLOGON", "/RL", "HIGHEST",
+	])
+}
+
+/// Deletes the task [`enable_task_autostart`] created under the given name.
+///
+/// Corresponds to [schtasks delete](https://learn.microsoft.com/en-us/windows-server/administration/windows-commands/schtasks-delete).
+pub fn disable_task_autostart(name: &str) -> io::Result<()> {
+	schtasks(&["/Delete", "/F", "/TN", name])
+}
+
+/// Runs `schtasks.exe` with the arguments, failing with its error output if it fails.
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+fn schtasks(args: &[&str]) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let output = Command::new("schtasks.exe").args(args).output()?;
+	if output.status.success() {
+		Ok(())
+	} else {
+		Err(io::Error::other(
+			String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+		))
+	}
+}
+
+/// Stops Windows from running the command [`enable_autostart`] set under the given name, if any.
+///
+/// Corresponds to [RegDeleteKeyValueW](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regdeletekeyvaluew).
+pub fn disable_autostart(name: &str) -> io::Result<()> {
+	let name = registry::wide(name);
+	registry::delete_value(HKEY_CURRENT_USER, RUN_KEY, PCWSTR(name.as_ptr()))
+}
@@ -0,0 +1,206 @@
+use std::{error::Error, fmt};
+
+use crate::{Hotkey, Key, KeyboardLayout, Modifiers};
+
+/// Builds a [`Hotkey`] step by step, see [`Hotkey::builder`].
+///
+/// E.g. `Hotkey::builder().ctrl().alt().key(keys::L).build()`.
+#[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct HotkeyBuilder {
+	modifiers: Modifiers,
+	key:       Option<KeySource>,
+}
+
+/// How the builder's key is given.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+enum KeySource {
+	Key(Key),
+	Char(char),
+}
+
+impl HotkeyBuilder {
+	/// Creates a builder with no modifiers nor key.
+	pub const fn new() -> Self {
+		Self {
+			modifiers: Modifiers::empty(),
+			key:       None,
+		}
+	}
+
+	/// Adds the [`Control`](Modifiers::Control) modifier.
+	pub const fn ctrl(self) -> Self { self.modifier(Modifiers::Control) }
+
+	/// Adds the [`Alt`](Modifiers::Alt) modifier.
+	pub const fn alt(self) -> Self { self.modifier(Modifiers::Alt) }
+
+	/// Adds the [`Shift`](Modifiers::Shift) modifier.
+	pub const fn shift(self) -> Self { self.modifier(Modifiers::Shift) }
+
+	/// Adds the [`Win`](Modifiers::Win) modifier.
+	pub const fn win(self) -> Self { self.modifier(Modifiers::Win) }
+
+	/// Adds the [`NoRepeat`](Modifiers::NoRepeat) modifier.
+	pub const fn no_repeat(self) -> Self { self.modifier(Modifiers::NoRepeat) }
+
+	/// Adds the modifiers.
+	pub const fn modifier(mut self, modifiers: Modifiers) -> Self {
+		self.modifiers = self.modifiers.union(modifiers);
+		self
+	}
+
+	/// Sets the key, replacing any previously set key or character.
+	pub const fn key(mut self, key: Key) -> Self {
+		self.key = Some(KeySource::Key(key));
+		self
+	}
+
+	/// Sets the key to the hotkey's and adds its modifiers, replacing any previously set key or character.
+	pub const fn hotkey(self, hotkey: Hotkey) -> Self {
+		self.modifier(hotkey.modifiers).key(hotkey.key_code)
+	}
+
+	/// Sets the key to the one that emits the character, replacing any previously set key or character.
+	///
+	/// It's resolved by [`build`](HotkeyBuilder::build) on the thread's current keyboard layout, along with the modifiers
+	/// the character requires, as with [`Key::from_current_layout_char_with_mods`].
+	pub const fn char(mut self, c: char) -> Self {
+		self.key = Some(KeySource::Char(c));
+		self
+	}
+
+	/// Builds the hotkey, resolving its character if it's given one.
+	pub fn build(self) -> Result<Hotkey, BuildHotkeyError> {
+		self.build_on(KeyboardLayout::current())
+	}
+
+	/// Builds the hotkey, resolving its character on the given keyboard layout if it's given one.
+	///
+	/// See [`Key::from_layout_char_with_mods`].
+	pub fn build_on(self, layout: KeyboardLayout) -> Result<Hotkey, BuildHotkeyError> {
+		let (key_code, modifiers) = match self.key.ok_or(BuildHotkeyError::MissingKey)? {
+			KeySource::Key(key) => (key, Modifiers::empty()),
+			KeySource::Char(c) => Key::from_layout_char_with_mods(c, layout)
+				.ok_or(BuildHotkeyError::UnmappableChar(c))?,
+		};
+		Ok(Hotkey::new(self.modifiers | modifiers, key_code))
+	}
+}
+
+/// An error [`build`](HotkeyBuilder::build)ing a [`Hotkey`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum BuildHotkeyError {
+	/// Neither a key nor a character was set.
+	MissingKey,
+	/// No key emits the character on the keyboard layout.
+	UnmappableChar(char),
+}
+
+impl fmt::Display for BuildHotkeyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MissingKey => f.write_str("the hotkey is missing a key"),
+			Self::UnmappableChar(c) => {
+				write!(f, "no key emits {c:?} on the keyboard layout")
+			}
+		}
+	}
+}
+
+impl Error for BuildHotkeyError {}
+
+/// A hotkey given piecewise, as by command line flags: a whole hotkey, a virtual key code, or a character, plus modifier
+/// flags that add to it, e.g. winlock's `--hotkey`, `-v`, `-k`, and `-c`/`-s`/`-w`/`-a`.
+#[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct HotkeyOptions {
+	/// A whole hotkey, whose modifiers the flags add to.
+	pub hotkey:       Option<Hotkey>,
+	/// The key's [virtual key code](https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes), taken
+	/// as is.
+	pub virtual_code: Option<u32>,
+	/// The character of the key, resolved on a keyboard layout along with the modifiers it requires.
+	pub key:          Option<char>,
+	/// Whether to add [`Control`](Modifiers::Control).
+	pub ctrl:         bool,
+	/// Whether to add [`Shift`](Modifiers::Shift).
+	pub shift:        bool,
+	/// Whether to add [`Win`](Modifiers::Win).
+	pub win:          bool,
+	/// Whether to add [`Alt`](Modifiers::Alt).
+	pub alt:          bool,
+	/// Whether the hotkey triggers repeatedly while held down, otherwise it has [`NoRepeat`](Modifiers::NoRepeat).
+	pub repeat:       bool,
+}
+
+impl HotkeyOptions {
+	/// The builder of the hotkey, [`None`] if no key is given.
+	///
+	/// It fails with [`Conflict`](OptionsKeyError::Conflict) if more than one of the hotkey, the virtual code, and the
+	/// character is given.
+	pub fn builder(self) -> Result<Option<HotkeyBuilder>, OptionsKeyError> {
+		let builder = Hotkey::builder().modifier(Modifiers::from(self));
+		match (self.hotkey, self.virtual_code, self.key) {
+			(None, None, None) => Ok(None),
+			(None, None, Some(c)) => Ok(Some(builder.char(c))),
+			(None, Some(code), None) => Ok(Some(builder.key(Key(code)))),
+			(Some(hotkey), None, None) => Ok(Some(builder.hotkey(hotkey))),
+			_ => Err(OptionsKeyError::Conflict),
+		}
+	}
+
+	/// The hotkey, [`None`] if no key is given, resolving the character on the thread's current keyboard layout.
+	///
+	/// See [`into_hotkey_on`](HotkeyOptions::into_hotkey_on).
+	pub fn into_hotkey(self) -> Result<Option<Hotkey>, OptionsKeyError> {
+		self.into_hotkey_on(KeyboardLayout::current())
+	}
+
+	/// The hotkey, [`None`] if no key is given, resolving the character on the given keyboard layout.
+	///
+	/// It fails with [`MappingFail`](OptionsKeyError::MappingFail) if no key emits the character, and see
+	/// [`builder`](HotkeyOptions::builder).
+	pub fn into_hotkey_on(self, layout: KeyboardLayout) -> Result<Option<Hotkey>, OptionsKeyError> {
+		self.builder()?
+			.map(|builder| {
+				builder
+					.build_on(layout)
+					.map_err(|_| OptionsKeyError::MappingFail)
+			})
+			.transpose()
+	}
+}
+
+impl From<HotkeyOptions> for Modifiers {
+	/// The modifier flags, with [`NoRepeat`](Modifiers::NoRepeat) unless [`repeat`](HotkeyOptions::repeat) is set.
+	fn from(value: HotkeyOptions) -> Self {
+		[
+			(!value.repeat, Modifiers::NoRepeat),
+			(value.ctrl, Modifiers::Control),
+			(value.shift, Modifiers::Shift),
+			(value.win, Modifiers::Win),
+			(value.alt, Modifiers::Alt),
+		]
+		.into_iter()
+		.filter_map(|(set, modifier)| set.then_some(modifier))
+		.collect()
+	}
+}
+
+/// An error of converting [`HotkeyOptions`] into a [`Hotkey`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum OptionsKeyError {
+	/// No key emits the character on the keyboard layout.
+	MappingFail,
+	/// More than one of the hotkey, the virtual code, and the character were given.
+	Conflict,
+}
+
+impl fmt::Display for OptionsKeyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::MappingFail => "failed to map the key to its virtual code",
+			Self::Conflict => "two keyboard shortcuts were given",
+		})
+	}
+}
+
+impl Error for OptionsKeyError {}
@@ -0,0 +1,96 @@
+use std::{cell::Cell, io};
+
+use windows::Win32::{
+	Foundation::{LPARAM, LRESULT, WPARAM},
+	System::Threading::GetCurrentThreadId,
+	UI::{
+		Input::KeyboardAndMouse::VIRTUAL_KEY,
+		WindowsAndMessaging::{
+			CallNextHookEx, GetMessageW, PostThreadMessageW, SetWindowsHookExW,
+			UnhookWindowsHookEx, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, MSG, WH_KEYBOARD_LL,
+			WM_KEYDOWN, WM_NULL, WM_SYSKEYDOWN,
+		},
+	},
+};
+
+use crate::{
+	hook::{held_modifiers, MODIFIER_KEYS},
+	Hotkey, Key,
+};
+
+/// A key press captured by [`capture_hotkey`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct CapturedKey {
+	/// The pressed key, and the modifiers that were held.
+	pub hotkey:    Hotkey,
+	/// The hardware scan code of the key.
+	pub scan_code: u32,
+}
+
+thread_local! {
+	/// The key press the hook of the current thread captured.
+	static CAPTURED: Cell<Option<CapturedKey>> = const { Cell::new(None) };
+}
+
+/// Blocks until a non-modifier key is pressed, and returns it along with the held modifiers.
+///
+/// It installs a [low-level keyboard hook](https://learn.microsoft.com/en-us/windows/win32/winmsg/lowlevelkeyboardproc)
+/// while waiting, so it captures presses regardless of which window is focused. The press still reaches that window.
+/// Other messages of the thread are retrieved and discarded meanwhile.
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn capture_hotkey() -> io::Result<CapturedKey> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let hook = unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_procedure), None, 0) }?;
+	let _hook = HookGuard(hook);
+	CAPTURED.with(|captured| captured.set(None));
+	let mut message = MSG::default();
+	loop {
+		if let Some(key) = CAPTURED.with(Cell::take) {
+			return Ok(key);
+		}
+		match unsafe { GetMessageW(&mut message, None, 0, 0) }.0 {
+			0 => {
+				return Err(io::Error::new(
+					io::ErrorKind::Interrupted,
+					"got a quit signal before a key press",
+				))
+			}
+			-1 => return Err(io::Error::last_os_error()),
+			_ => {}
+		}
+	}
+}
+
+/// Uninstalls the hook when dropped.
+struct HookGuard(HHOOK);
+
+impl Drop for HookGuard {
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	fn drop(&mut self) {
+		#[cfg(not(windows))]
+		return;
+		unsafe { UnhookWindowsHookEx(self.0) };
+	}
+}
+
+unsafe extern "system" fn hook_procedure(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+	let key_down = wparam.0 == WM_KEYDOWN as usize || wparam.0 == WM_SYSKEYDOWN as usize;
+	if code == HC_ACTION as i32 && key_down {
+		let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+		if !MODIFIER_KEYS.contains(&VIRTUAL_KEY(info.vkCode as _)) {
+			CAPTURED.with(|captured| {
+				captured.set(Some(CapturedKey {
+					hotkey:    Hotkey {
+						modifiers: held_modifiers(),
+						key_code:  Key(info.vkCode),
+					},
+					scan_code: info.scanCode,
+				}))
+			});
+			// wake up the message loop of capture_hotkey
+			PostThreadMessageW(GetCurrentThreadId(), WM_NULL, WPARAM(0), LPARAM(0));
+		}
+	}
+	CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
@@ -0,0 +1,38 @@
+use std::time::{Duration, Instant};
+
+/// Keeps events from being handled more often than once per cooldown, e.g. hotkey presses in quick succession.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Debounce {
+	cooldown: Duration,
+	/// When the last event was let through.
+	last:     Option<Instant>,
+}
+
+impl Debounce {
+	/// Creates a debounce that lets the first event through, and then events at least the cooldown after the last one it
+	/// let through.
+	pub const fn new(cooldown: Duration) -> Self {
+		Self {
+			cooldown,
+			last: None,
+		}
+	}
+
+	/// Whether an event that happens now should be handled, which starts a cooldown if so.
+	///
+	/// Events during a cooldown don't extend it.
+	pub fn ready(&mut self) -> bool { self.ready_at(Instant::now()) }
+
+	/// Whether an event that happens at the given instant should be handled, which starts a cooldown if so.
+	///
+	/// See [`ready`](Debounce::ready).
+	pub fn ready_at(&mut self, now: Instant) -> bool {
+		let ready = self
+			.last
+			.is_none_or(|last| now.saturating_duration_since(last) >= self.cooldown);
+		if ready {
+			self.last = Some(now);
+		}
+		ready
+	}
+}
@@ -0,0 +1,211 @@
+use std::{cell::RefCell, io};
+
+use windows::Win32::{
+	Foundation::{LPARAM, LRESULT, WPARAM},
+	System::Threading::GetCurrentThreadId,
+	UI::{
+		Input::KeyboardAndMouse::{
+			GetAsyncKeyState, VIRTUAL_KEY, VK_CONTROL, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN,
+			VK_MENU, VK_RCONTROL, VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SHIFT,
+		},
+		WindowsAndMessaging::{
+			CallNextHookEx, PostThreadMessageW, SetWindowsHookExW, UnhookWindowsHookEx, HC_ACTION,
+			HHOOK, KBDLLHOOKSTRUCT, LLKHF_EXTENDED, WH_KEYBOARD_LL, WM_HOTKEY, WM_KEYDOWN,
+			WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP,
+		},
+	},
+};
+
+use crate::{Hotkey, HotkeyId, Key, Modifiers, RegisterError, WM_HOTKEY_RELEASED};
+
+/// The `lParam` bit that marks a hotkey message as posted by the hook, past the modifiers' bits.
+pub(crate) const HOOK_MESSAGE_FLAG: isize = 0x1000;
+/// The `lParam` bit of a hook's hotkey message that's set if its key event had the extended flag.
+pub(crate) const EXTENDED_KEY_FLAG: isize = 0x2000;
+
+/// The modifier keys.
+pub(crate) const MODIFIER_KEYS: [VIRTUAL_KEY; 11] = [
+	VK_SHIFT,
+	VK_CONTROL,
+	VK_MENU,
+	VK_LSHIFT,
+	VK_RSHIFT,
+	VK_LCONTROL,
+	VK_RCONTROL,
+	VK_LMENU,
+	VK_RMENU,
+	VK_LWIN,
+	VK_RWIN,
+];
+
+/// Whether the key is currently held down.
+fn is_down(key: VIRTUAL_KEY) -> bool { (unsafe { GetAsyncKeyState(key.0 as _) }) < 0 }
+
+/// The modifiers that are currently held down.
+///
+/// In a low-level keyboard hook, the key being reported isn't considered held down yet.
+pub(crate) fn held_modifiers() -> Modifiers {
+	let mut modifiers = Modifiers::empty();
+	if is_down(VK_CONTROL) {
+		modifiers |= Modifiers::Control;
+	}
+	if is_down(VK_MENU) {
+		modifiers |= Modifiers::Alt;
+	}
+	if is_down(VK_SHIFT) {
+		modifiers |= Modifiers::Shift;
+	}
+	if is_down(VK_LWIN) || is_down(VK_RWIN) {
+		modifiers |= Modifiers::Win;
+	}
+	modifiers
+}
+
+/// A hotkey registered to the hook.
+#[derive(Debug)]
+struct HookedHotkey {
+	id:       HotkeyId,
+	hotkey:   Hotkey,
+	/// Whether the key is held down since it triggered the hotkey, for [`Modifiers::NoRepeat`].
+	pressed:  bool,
+	/// Whether to keep the key events that trigger the hotkey from reaching other applications.
+	suppress: bool,
+	/// Whether to report the hotkey's releases too.
+	release:  bool,
+}
+
+/// The hook of the current thread and the hotkeys registered to it.
+#[derive(Debug, Default)]
+struct Hook {
+	handle:  Option<HHOOK>,
+	hotkeys: Vec<HookedHotkey>,
+}
+
+thread_local! {
+	static HOOK: RefCell<Hook> = RefCell::default();
+}
+
+/// Registers the hotkey to the current thread's hook, installing it if it's the first hotkey.
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub(crate) fn register(
+	hotkey: Hotkey,
+	id: HotkeyId,
+	suppress: bool,
+	release: bool,
+) -> Result<(), RegisterError> {
+	#[cfg(not(windows))]
+	return Err(RegisterError::Os(crate::unsupported()));
+	hotkey.check_modifiers()?;
+	HOOK.with(|hook| {
+		let mut hook = hook.borrow_mut();
+		if hook.hotkeys.iter().any(|hooked| hooked.id == id) {
+			return Err(RegisterError::AlreadyRegistered);
+		}
+		if hook.handle.is_none() {
+			let handle =
+				unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_procedure), None, 0) }
+					.map_err(io::Error::from)?;
+			hook.handle = Some(handle);
+		}
+		hook.hotkeys.push(HookedHotkey {
+			id,
+			hotkey,
+			pressed: false,
+			suppress,
+			release,
+		});
+		Ok(())
+	})
+}
+
+/// Unregisters the hotkey from the current thread's hook, uninstalling it if it was the last hotkey.
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub(crate) fn unregister(id: HotkeyId) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	HOOK.with(|hook| {
+		let mut hook = hook.borrow_mut();
+		let index = hook
+			.hotkeys
+			.iter()
+			.position(|hooked| hooked.id == id)
+			.ok_or_else(|| {
+				io::Error::new(io::ErrorKind::NotFound, "no hotkey is hooked under the id")
+			})?;
+		hook.hotkeys.remove(index);
+		if hook.hotkeys.is_empty() {
+			if let Some(handle) = hook.handle.take() {
+				if !unsafe { UnhookWindowsHookEx(handle) }.as_bool() {
+					return Err(io::Error::last_os_error());
+				}
+			}
+		}
+		Ok(())
+	})
+}
+
+unsafe extern "system" fn hook_procedure(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+	let mut suppress = false;
+	if code == HC_ACTION as i32 {
+		let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+		let message = wparam.0 as u32;
+		let key_down = message == WM_KEYDOWN || message == WM_SYSKEYDOWN;
+		let key_up = message == WM_KEYUP || message == WM_SYSKEYUP;
+		// same parameters as a RegisterHotKey notification, marked with the hook's flags
+		let flags = HOOK_MESSAGE_FLAG
+			| if info.flags.contains(LLKHF_EXTENDED) {
+				EXTENDED_KEY_FLAG
+			} else {
+				0
+			};
+		HOOK.with(|hook| {
+			let mut hook = hook.borrow_mut();
+			let key = Key(info.vkCode);
+			let matching = hook
+				.hotkeys
+				.iter_mut()
+				.filter(|hooked| hooked.hotkey.key_code == key);
+			if key_down {
+				let modifiers = held_modifiers();
+				for hooked in matching {
+					if !hooked.hotkey.is_triggered_by(key, modifiers) {
+						continue;
+					}
+					let repeat = std::mem::replace(&mut hooked.pressed, true);
+					suppress |= hooked.suppress;
+					if repeat && hooked.hotkey.modifiers.contains(Modifiers::NoRepeat) {
+						continue;
+					}
+					let lparam = (info.vkCode << 16) as isize | modifiers.bits() as isize | flags;
+					PostThreadMessageW(
+						GetCurrentThreadId(),
+						WM_HOTKEY,
+						WPARAM(hooked.id.0 as _),
+						LPARAM(lparam),
+					);
+				}
+			} else if key_up {
+				let modifiers = held_modifiers();
+				for hooked in matching {
+					let pressed = std::mem::replace(&mut hooked.pressed, false);
+					suppress |= hooked.suppress && pressed;
+					if pressed && hooked.release {
+						let lparam =
+							(info.vkCode << 16) as isize | modifiers.bits() as isize | flags;
+						PostThreadMessageW(
+							GetCurrentThreadId(),
+							WM_HOTKEY_RELEASED,
+							WPARAM(hooked.id.0 as _),
+							LPARAM(lparam),
+						);
+					}
+				}
+			}
+		});
+	}
+	if suppress {
+		LRESULT(1)
+	} else {
+		CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+	}
+}
@@ -0,0 +1,146 @@
+//! Named [`Key`]s.
+//!
+//! See [`Key::from_name`] to look them up by name.
+
+use crate::Key;
+
+/// The named keys, each key's canonical name followed by its aliases, in order of virtual code.
+pub fn names() -> impl Iterator<Item = (&'static str, Key)> { NAMES.iter().copied() }
+
+macro_rules! keys {
+	($($constant:ident = $code:literal, $name:literal $(| $alias:literal)*;)*) => {
+		$(
+			#[doc = concat!("The <kbd>", $name, "</kbd> key.")]
+			pub const $constant: Key = Key($code);
+		)*
+
+		/// The key names, each key's canonical name followed by its aliases.
+		pub(crate) const NAMES: &[(&str, Key)] = &[$(($name, $constant), $(($alias, $constant),)*)*];
+	};
+}
+
+keys! {
+	BACKSPACE = 0x08, "Backspace" | "Back";
+	TAB = 0x09, "Tab";
+	ENTER = 0x0D, "Enter" | "Return";
+	PAUSE = 0x13, "Pause" | "Break";
+	CAPS_LOCK = 0x14, "CapsLock" | "Caps";
+	ESCAPE = 0x1B, "Escape" | "Esc";
+	SPACE = 0x20, "Space";
+	PAGE_UP = 0x21, "PageUp" | "PgUp";
+	PAGE_DOWN = 0x22, "PageDown" | "PgDn";
+	END = 0x23, "End";
+	HOME = 0x24, "Home";
+	LEFT = 0x25, "Left" | "ArrowLeft";
+	UP = 0x26, "Up" | "ArrowUp";
+	RIGHT = 0x27, "Right" | "ArrowRight";
+	DOWN = 0x28, "Down" | "ArrowDown";
+	PRINT_SCREEN = 0x2C, "PrintScreen" | "PrtSc";
+	INSERT = 0x2D, "Insert" | "Ins";
+	DELETE = 0x2E, "Delete" | "Del";
+	DIGIT_0 = 0x30, "0";
+	DIGIT_1 = 0x31, "1";
+	DIGIT_2 = 0x32, "2";
+	DIGIT_3 = 0x33, "3";
+	DIGIT_4 = 0x34, "4";
+	DIGIT_5 = 0x35, "5";
+	DIGIT_6 = 0x36, "6";
+	DIGIT_7 = 0x37, "7";
+	DIGIT_8 = 0x38, "8";
+	DIGIT_9 = 0x39, "9";
+	A = 0x41, "A";
+	B = 0x42, "B";
+	C = 0x43, "C";
+	D = 0x44, "D";
+	E = 0x45, "E";
+	F = 0x46, "F";
+	G = 0x47, "G";
+	H = 0x48, "H";
+	I = 0x49, "I";
+	J = 0x4A, "J";
+	K = 0x4B, "K";
+	L = 0x4C, "L";
+	M = 0x4D, "M";
+	N = 0x4E, "N";
+	O = 0x4F, "O";
+	P = 0x50, "P";
+	Q = 0x51, "Q";
+	R = 0x52, "R";
+	S = 0x53, "S";
+	T = 0x54, "T";
+	U = 0x55, "U";
+	V = 0x56, "V";
+	W = 0x57, "W";
+	X = 0x58, "X";
+	Y = 0x59, "Y";
+	Z = 0x5A, "Z";
+	LEFT_WIN = 0x5B, "LWin" | "LeftWin";
+	RIGHT_WIN = 0x5C, "RWin" | "RightWin";
+	APPS = 0x5D, "Apps" | "Menu";
+	NUMPAD_0 = 0x60, "Num0" | "Numpad0";
+	NUMPAD_1 = 0x61, "Num1" | "Numpad1";
+	NUMPAD_2 = 0x62, "Num2" | "Numpad2";
+	NUMPAD_3 = 0x63, "Num3" | "Numpad3";
+	NUMPAD_4 = 0x64, "Num4" | "Numpad4";
+	NUMPAD_5 = 0x65, "Num5" | "Numpad5";
+	NUMPAD_6 = 0x66, "Num6" | "Numpad6";
+	NUMPAD_7 = 0x67, "Num7" | "Numpad7";
+	NUMPAD_8 = 0x68, "Num8" | "Numpad8";
+	NUMPAD_9 = 0x69, "Num9" | "Numpad9";
+	NUMPAD_MULTIPLY = 0x6A, "NumpadMultiply" | "NumMultiply";
+	NUMPAD_ADD = 0x6B, "NumpadAdd" | "NumAdd";
+	NUMPAD_SEPARATOR = 0x6C, "NumpadSeparator" | "NumSeparator";
+	NUMPAD_SUBTRACT = 0x6D, "NumpadSubtract" | "NumSubtract";
+	NUMPAD_DECIMAL = 0x6E, "NumpadDecimal" | "NumDecimal";
+	NUMPAD_DIVIDE = 0x6F, "NumpadDivide" | "NumDivide";
+	F1 = 0x70, "F1";
+	F2 = 0x71, "F2";
+	F3 = 0x72, "F3";
+	F4 = 0x73, "F4";
+	F5 = 0x74, "F5";
+	F6 = 0x75, "F6";
+	F7 = 0x76, "F7";
+	F8 = 0x77, "F8";
+	F9 = 0x78, "F9";
+	F10 = 0x79, "F10";
+	F11 = 0x7A, "F11";
+	F12 = 0x7B, "F12";
+	F13 = 0x7C, "F13";
+	F14 = 0x7D, "F14";
+	F15 = 0x7E, "F15";
+	F16 = 0x7F, "F16";
+	F17 = 0x80, "F17";
+	F18 = 0x81, "F18";
+	F19 = 0x82, "F19";
+	F20 = 0x83, "F20";
+	F21 = 0x84, "F21";
+	F22 = 0x85, "F22";
+	F23 = 0x86, "F23";
+	F24 = 0x87, "F24";
+	NUM_LOCK = 0x90, "NumLock";
+	SCROLL_LOCK = 0x91, "ScrollLock";
+	LEFT_SHIFT = 0xA0, "LShift" | "LeftShift";
+	RIGHT_SHIFT = 0xA1, "RShift" | "RightShift";
+	LEFT_CONTROL = 0xA2, "LCtrl" | "LeftCtrl" | "LControl" | "LeftControl";
+	RIGHT_CONTROL = 0xA3, "RCtrl" | "RightCtrl" | "RControl" | "RightControl";
+	LEFT_ALT = 0xA4, "LAlt" | "LeftAlt";
+	RIGHT_ALT = 0xA5, "RAlt" | "RightAlt" | "AltGr";
+	BROWSER_BACK = 0xA6, "BrowserBack";
+	BROWSER_FORWARD = 0xA7, "BrowserForward";
+	BROWSER_REFRESH = 0xA8, "BrowserRefresh";
+	BROWSER_STOP = 0xA9, "BrowserStop";
+	BROWSER_SEARCH = 0xAA, "BrowserSearch";
+	BROWSER_FAVORITES = 0xAB, "BrowserFavorites";
+	BROWSER_HOME = 0xAC, "BrowserHome";
+	VOLUME_MUTE = 0xAD, "VolumeMute" | "Mute";
+	VOLUME_DOWN = 0xAE, "VolumeDown";
+	VOLUME_UP = 0xAF, "VolumeUp";
+	MEDIA_NEXT_TRACK = 0xB0, "MediaNextTrack" | "NextTrack";
+	MEDIA_PREV_TRACK = 0xB1, "MediaPrevTrack" | "PrevTrack";
+	MEDIA_STOP = 0xB2, "MediaStop";
+	MEDIA_PLAY_PAUSE = 0xB3, "MediaPlayPause" | "PlayPause";
+	LAUNCH_MAIL = 0xB4, "LaunchMail" | "Mail";
+	LAUNCH_MEDIA_SELECT = 0xB5, "LaunchMediaSelect" | "MediaSelect";
+	LAUNCH_APP1 = 0xB6, "LaunchApp1";
+	LAUNCH_APP2 = 0xB7, "LaunchApp2";
+}
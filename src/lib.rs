@@ -4,20 +4,93 @@
 #![doc = include_str!("../README.md")]
 #![deny(missing_docs)]
 
-#[cfg(not(target_family = "windows"))]
-compile_error!("This library targets Windows only.");
+#[cfg(not(any(windows, feature = "stub")))]
+compile_error!(
+	"This library targets Windows only. Enable the `stub` feature to build it elsewhere."
+);
+
+mod actions;
+mod api;
+mod audit;
+mod autostart;
+mod builder;
+mod capture;
+mod debounce;
+mod hook;
+pub mod keys;
+mod listener;
+mod manager;
+mod power;
+mod registration;
+mod registry;
+mod session;
+mod watch;
+mod window;
+
+pub use actions::{
+	beep, disconnect_all_sessions, disconnect_session, log_off, monitor_off, monitor_on, restart,
+	shutdown, sleep, start_screensaver, Action, ParseActionError,
+};
+#[cfg(feature = "mock")]
+pub use api::MockApi;
+pub use api::{Message, SystemApi, WinApi};
+pub use audit::AuditLogger;
+pub use autostart::{
+	disable_autostart, disable_task_autostart, enable_autostart, enable_task_autostart,
+};
+pub use builder::{BuildHotkeyError, HotkeyBuilder, HotkeyOptions, OptionsKeyError};
+pub use capture::{capture_hotkey, CapturedKey};
+pub use debounce::Debounce;
+#[cfg(feature = "tokio")]
+pub use listener::AsyncListener;
+pub use listener::{spawn, Listener};
+pub use manager::{HotkeyManager, ManagerEvent};
+pub use power::{PowerEvent, PowerNotifications};
+pub use registration::Registration;
+pub use session::{idle_duration, is_workstation_locked, SessionEvent, SessionNotifications};
+pub use watch::{watch_lock_policy, PolicyWatcher};
+pub use window::HotkeyWindow;
+/// The raw handle a [`KeyboardLayout`] converts from and into, re-exported so that doesn't require the `windows` crate.
+pub use windows::Win32::UI::TextServices::HKL;
 
-use std::{io, mem};
+use std::{
+	error::Error,
+	fmt, io, iter, mem,
+	str::FromStr,
+	sync::atomic::{AtomicI32, Ordering},
+	thread,
+	time::{Duration, Instant},
+};
 
 use bitflags::bitflags;
 use windows::{
+	core::PCWSTR,
 	w,
 	Win32::{
-		Foundation::HWND,
-		System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_DWORD},
+		Foundation::{
+			CloseHandle, ERROR_ACCESS_DENIED, ERROR_HOTKEY_ALREADY_REGISTERED, HANDLE, HWND,
+			LPARAM, WAIT_FAILED, WIN32_ERROR, WPARAM,
+		},
+		Globalization::{GetLocaleInfoW, LOCALE_SLOCALIZEDDISPLAYNAME},
+		Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY},
+		System::{
+			Registry::{HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE},
+			Threading::{GetCurrentProcess, GetCurrentThreadId, OpenProcessToken, INFINITE},
+		},
 		UI::{
-			Input::KeyboardAndMouse::{RegisterHotKey, VkKeyScanW, HOT_KEY_MODIFIERS},
-			WindowsAndMessaging::{GetMessageW, WM_HOTKEY, WM_QUIT},
+			Input::KeyboardAndMouse::{
+				GetKeyboardLayout, GetKeyboardLayoutList, LoadKeyboardLayoutW, MapVirtualKeyW,
+				UnloadKeyboardLayout, VkKeyScanExW, HOT_KEY_MODIFIERS, KLF_NOTELLSHELL,
+				MAPVK_VK_TO_CHAR,
+			},
+			WindowsAndMessaging::{
+				GetForegroundWindow, GetMessageW, GetWindowThreadProcessId,
+				MsgWaitForMultipleObjectsEx, PeekMessageW, PostThreadMessageW,
+				MSG_WAIT_FOR_MULTIPLE_OBJECTS_EX_FLAGS, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND,
+				PM_REMOVE, QS_HOTKEY, QS_POSTMESSAGE, QS_SENDMESSAGE, WM_APP, WM_HOTKEY,
+				WM_POWERBROADCAST, WM_QUIT, WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK,
+				WTS_SESSION_UNLOCK,
+			},
 		},
 	},
 };
@@ -26,7 +99,10 @@ bitflags! {
 /// Keyboard [`Hotkey`] modifiers.
 ///
 /// Corresponds with [`RegisterHotKey`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey)'s [`fsModifiers`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey#:~:text=the%20action%20taken.-,%5Bin%5D%20fsModifiers,-Type%3A%20UINT) parameter.
+///
+/// With the `serde` feature, it (de)serializes as its flag names, e.g. `"Control | Alt"` in human-readable formats.
 #[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct Modifiers: u32 {
 	/// Either <kbd>ALT</kbd> key must be held down.
 	const Alt = 0x0001;
@@ -47,29 +123,475 @@ impl From<Modifiers> for HOT_KEY_MODIFIERS {
 	fn from(value: Modifiers) -> Self { Self(value.bits()) }
 }
 
+impl Modifiers {
+	/// Whether <kbd>CTRL</kbd> is among the modifiers.
+	pub const fn has_ctrl(self) -> bool { self.contains(Self::Control) }
+
+	/// Whether <kbd>ALT</kbd> is among the modifiers.
+	pub const fn has_alt(self) -> bool { self.contains(Self::Alt) }
+
+	/// Whether <kbd>SHIFT</kbd> is among the modifiers.
+	pub const fn has_shift(self) -> bool { self.contains(Self::Shift) }
+
+	/// Whether <kbd>WINDOWS</kbd> is among the modifiers.
+	pub const fn has_win(self) -> bool { self.contains(Self::Win) }
+
+	/// The names of the modifier keys, e.g. `Ctrl` and `Alt`, in the order they're displayed.
+	///
+	/// Unlike the flag names of [`iter_names`](Modifiers::iter_names), it omits [`NoRepeat`](Modifiers::NoRepeat), which
+	/// isn't a key.
+	pub fn names(self) -> impl Iterator<Item = &'static str> {
+		[
+			(self.has_ctrl(), "Ctrl"),
+			(self.has_alt(), "Alt"),
+			(self.has_shift(), "Shift"),
+			(self.has_win(), "Win"),
+		]
+		.into_iter()
+		.filter_map(|(has, name)| has.then_some(name))
+	}
+
+	/// Gets the modifier by its name, case-insensitively.
+	fn parse_name(name: &str) -> Option<Self> {
+		[
+			("ctrl", Self::Control),
+			("control", Self::Control),
+			("alt", Self::Alt),
+			("option", Self::Alt),
+			("opt", Self::Alt),
+			("shift", Self::Shift),
+			("win", Self::Win),
+			("windows", Self::Win),
+			("super", Self::Win),
+			("meta", Self::Win),
+			("cmd", Self::Win),
+			("command", Self::Win),
+		]
+		.into_iter()
+		.find_map(|(candidate, modifier)| candidate.eq_ignore_ascii_case(name).then_some(modifier))
+	}
+
+	/// Combines the modifiers of the names, case-insensitively, e.g. `["Ctrl", "Alt"]`, failing at the first unknown one.
+	///
+	/// The names are those of [`Display`](fmt::Display) and aliases from other systems: `Control` for `Ctrl`, `Option`
+	/// and `Opt` for `Alt`, and `Windows`, `Super`, `Meta`, `Cmd`, and `Command` for `Win`. Unlike bitflags'
+	/// [`from_name`](Modifiers::from_name), it doesn't take the flags' names. Modifiers also
+	/// [`collect`](Iterator::collect) from an iterator of modifiers.
+	pub fn from_names<S: AsRef<str>>(
+		names: impl IntoIterator<Item = S>,
+	) -> Result<Self, ParseHotkeyError> {
+		names
+			.into_iter()
+			.try_fold(Self::empty(), |modifiers, name| {
+				let name = name.as_ref().trim();
+				Self::parse_name(name)
+					.map(|modifier| modifiers | modifier)
+					.ok_or_else(|| ParseHotkeyError::UnknownModifier(name.to_owned()))
+			})
+	}
+}
+
+impl fmt::Display for Modifiers {
+	/// Joins the modifiers with `+`, e.g. `Ctrl+Alt`.
+	///
+	/// [`NoRepeat`](Modifiers::NoRepeat) isn't a key so it's omitted.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut names = self.names();
+		if let Some(name) = names.next() {
+			f.write_str(name)?;
+		}
+		names.try_for_each(|name| write!(f, "+{name}"))
+	}
+}
+
+/// The modifier keys that are currently held down, on either side of the keyboard, e.g. to tell a stuck modifier.
+///
+/// It reads the physical keyboard state when called, regardless of the window in focus, and never includes
+/// [`NoRepeat`](Modifiers::NoRepeat).
+///
+/// Corresponds to [GetAsyncKeyState](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate).
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn modifiers_currently_down() -> Modifiers {
+	#[cfg(not(windows))]
+	return Modifiers::empty();
+	hook::held_modifiers()
+}
+
 #[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
 #[repr(transparent)]
 /// A keyboard key / button.
+///
+/// With the `serde` feature, it (de)serializes as its virtual key code.
 pub struct Key(
 	/// The key's [virtual key code](https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes).
 	pub u32,
 );
 
 impl Key {
+	/// Creates a [`Key`] from its [virtual key code](https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes).
+	pub const fn new(vk: u32) -> Self { Self(vk) }
+
 	/// Converts a [`char`] into a [`Key`].
 	///
-	/// It reads the user's current keyboard layout to determine the key that emits the given character.
+	/// It reads the thread's current keyboard layout to determine the key that emits the given character.
+	/// The result doesn't follow later layout switches, so to keep up with them resolve the character again, e.g. with
+	/// [`from_layout_char`](Key::from_layout_char) and the [`foreground`](KeyboardLayout::foreground) layout.
 	///
 	/// Corresponds to [VkKeyScanW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-vkkeyscanw).
 	pub fn from_current_layout_char(c: char) -> Option<Self> {
-		let scan = unsafe { VkKeyScanW(c as u16) };
-		let vkc = scan & 0x00FF;
-		(vkc != -1).then_some(Key(vkc as u32))
+		Self::from_current_layout_char_with_mods(c).map(|(key, _)| key)
+	}
+
+	/// Converts a [`char`] into a [`Key`] and the [`Modifiers`] that must be held for the key to emit it.
+	///
+	/// E.g. on a US layout `'?'` converts to the <kbd>/</kbd> key with [`Shift`](Modifiers::Shift).
+	/// Characters that require <kbd>AltGr</kbd> have both [`Control`](Modifiers::Control) and [`Alt`](Modifiers::Alt),
+	/// since that's what <kbd>AltGr</kbd> stands for.
+	///
+	/// See [`from_current_layout_char`](Key::from_current_layout_char).
+	pub fn from_current_layout_char_with_mods(c: char) -> Option<(Self, Modifiers)> {
+		Self::from_layout_char_with_mods(c, KeyboardLayout::current())
+	}
+
+	/// Converts a [`char`] into a [`Key`] on the given keyboard layout.
+	///
+	/// Characters beyond the Basic Multilingual Plane, e.g. emoji, convert to [`None`], since no key emits them alone.
+	///
+	/// Corresponds to [VkKeyScanExW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-vkkeyscanexw).
+	pub fn from_layout_char(c: char, layout: KeyboardLayout) -> Option<Self> {
+		Self::from_layout_char_with_mods(c, layout).map(|(key, _)| key)
+	}
+
+	/// Converts a [`char`] into a [`Key`] and the [`Modifiers`] that must be held for the key to emit it, on the given
+	/// keyboard layout.
+	///
+	/// See [`from_current_layout_char_with_mods`](Key::from_current_layout_char_with_mods).
+	#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+	pub fn from_layout_char_with_mods(
+		c: char,
+		layout: KeyboardLayout,
+	) -> Option<(Self, Modifiers)> {
+		#[cfg(not(windows))]
+		return None;
+		// characters beyond the Basic Multilingual Plane don't fit the API's single UTF-16 code unit
+		let c = u16::try_from(c as u32).ok()?;
+		let scan = unsafe { VkKeyScanExW(c, HKL::from(layout)) };
+		if scan == -1 {
+			return None;
+		}
+		let [vkc, shift_state] = (scan as u16).to_le_bytes();
+		let modifiers = [
+			(0b001, Modifiers::Shift),
+			(0b010, Modifiers::Control),
+			(0b100, Modifiers::Alt),
+		]
+		.into_iter()
+		.filter_map(|(bit, modifier)| (shift_state & bit != 0).then_some(modifier))
+		.collect();
+		Some((Key(vkc as u32), modifiers))
+	}
+
+	/// Converts the [`Key`] into the [`char`] it emits, without modifiers.
+	///
+	/// It reads the user's current keyboard layout to determine the character, and returns [`None`] for keys that don't emit one, such as
+	/// function keys and modifiers. Letters are unshifted, i.e. uppercase.
+	///
+	/// Corresponds to [MapVirtualKeyW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-mapvirtualkeyw) with `MAPVK_VK_TO_CHAR`.
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn to_char(self) -> Option<char> {
+		#[cfg(not(windows))]
+		return None;
+		// the high bit marks dead keys
+		let c = unsafe { MapVirtualKeyW(self.0, MAPVK_VK_TO_CHAR) } & !(1 << 31);
+		char::from_u32(c).filter(|&c| c != '\0')
+	}
+
+	/// Whether the key is an extended key, i.e. one whose key events carry the extended flag, e.g. <kbd>Right Alt</kbd>
+	/// and the navigation keys beside the numeric keypad.
+	///
+	/// It's based on the virtual key code, so it can't tell keys that share theirs apart, e.g. the numeric keypad's
+	/// <kbd>Enter</kbd> from the main one, or the keypad's arrows (with <kbd>Num Lock</kbd> off) from the dedicated
+	/// ones. The hook reports the actual flag of a press, see [`Message::extended_key`].
+	///
+	/// Corresponds to [Extended-Key Flag](https://learn.microsoft.com/en-us/windows/win32/inputdev/about-keyboard-input#extended-key-flag).
+	pub const fn is_extended(self) -> bool {
+		matches!(
+			self.0,
+			// Cancel (Ctrl+Break), the navigation keys, Print Screen, Insert, Delete
+			0x03 | 0x21..=0x28 | 0x2C..=0x2E
+			// the Windows keys, Apps, the keypad's Divide, Num Lock
+			| 0x5B..=0x5D | 0x6F | 0x90
+			// Right Ctrl, Right Alt, the browser, volume, media, and launch keys
+			| 0xA3 | 0xA5..=0xB7
+		)
+	}
+
+	/// The modifier the key holds down, if it's a modifier key, e.g. [`Win`](Modifiers::Win) for
+	/// [`LEFT_WIN`](keys::LEFT_WIN).
+	pub const fn modifier(self) -> Option<Modifiers> {
+		match self.0 {
+			0x10 | 0xA0 | 0xA1 => Some(Modifiers::Shift),
+			0x11 | 0xA2 | 0xA3 => Some(Modifiers::Control),
+			0x12 | 0xA4 | 0xA5 => Some(Modifiers::Alt),
+			0x5B | 0x5C => Some(Modifiers::Win),
+			_ => None,
+		}
+	}
+
+	/// Gets the key by its name, case-insensitively, e.g. `F5`, `Esc`, or `PageUp`.
+	///
+	/// See [`keys`] for the named keys.
+	pub fn from_name(name: &str) -> Option<Self> {
+		keys::NAMES
+			.iter()
+			.find_map(|&(candidate, key)| candidate.eq_ignore_ascii_case(name).then_some(key))
+	}
+
+	/// Gets the key's canonical name, if it's one of the [`keys`].
+	pub fn name(self) -> Option<&'static str> {
+		keys::NAMES
+			.iter()
+			.find_map(|&(name, key)| (key == self).then_some(name))
+	}
+
+	/// Parses the `VK(0x4C)` form of [`Key`]'s [`Display`](fmt::Display), case-insensitively.
+	fn parse_vk(s: &str) -> Option<Self> {
+		if !s.get(..3)?.eq_ignore_ascii_case("vk(") {
+			return None;
+		}
+		let code = s[3..].strip_suffix(')')?;
+		match code.get(..2) {
+			Some(prefix) if prefix.eq_ignore_ascii_case("0x") => {
+				u32::from_str_radix(&code[2..], 16)
+			}
+			_ => code.parse(),
+		}
+		.ok()
+		.map(Key)
+	}
+}
+
+impl fmt::Display for Key {
+	/// Writes the key's [`name`](Key::name), or the [`char`](Key::to_char) it emits for unnamed keys, or the `VK(0x4C)` form
+	/// otherwise.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(name) = self.name() {
+			f.write_str(name)
+		} else if let Some(c) = self
+			.to_char()
+			.filter(|c| !c.is_control() && !c.is_whitespace())
+		{
+			write!(f, "{c}")
+		} else {
+			write!(f, "VK(0x{:02X})", self.0)
+		}
+	}
+}
+
+impl TryFrom<char> for Key {
+	type Error = UnmappableChar;
+
+	/// Converts the character with [`Key::from_current_layout_char`].
+	fn try_from(value: char) -> Result<Self, Self::Error> {
+		Self::from_current_layout_char(value).ok_or(UnmappableChar(value))
+	}
+}
+
+/// The error of converting a [`char`] that no key of the current keyboard layout emits into a [`Key`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct UnmappableChar(pub char);
+
+impl fmt::Display for UnmappableChar {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"no key of the current keyboard layout emits {:?}",
+			self.0
+		)
 	}
 }
 
+impl Error for UnmappableChar {}
+
+/// A keyboard [input locale identifier](https://learn.microsoft.com/en-us/windows/win32/intl/language-identifiers), which
+/// determines the keys' characters.
+///
+/// Windows tracks the layout per thread, so the layout of a program in the background doesn't follow the user's switches,
+/// which the [`foreground`](KeyboardLayout::foreground) layout does.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct KeyboardLayout(isize);
+
+impl KeyboardLayout {
+	/// The calling thread's layout.
+	///
+	/// Corresponds to [GetKeyboardLayout](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeyboardlayout).
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn current() -> Self {
+		#[cfg(not(windows))]
+		return Self(0);
+		Self(unsafe { GetKeyboardLayout(0) }.0)
+	}
+
+	/// The layout of the foreground window's thread, i.e. the layout the user types with.
+	///
+	/// Falls back to the [`current`](KeyboardLayout::current) layout when there's no foreground window.
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn foreground() -> Self {
+		#[cfg(not(windows))]
+		return Self(0);
+		let thread = unsafe { GetWindowThreadProcessId(GetForegroundWindow(), None) };
+		Self(unsafe { GetKeyboardLayout(thread) }.0)
+	}
+
+	/// The layouts the user has installed, in the order the user switches between them.
+	///
+	/// To tell them apart to the user, see [`language_name`](KeyboardLayout::language_name).
+	///
+	/// Corresponds to [GetKeyboardLayoutList](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeyboardlayoutlist).
+	#[doc(alias = "installed_layouts")]
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn installed() -> io::Result<Vec<Self>> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let count = unsafe { GetKeyboardLayoutList(None) };
+		if count == 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let mut layouts = vec![HKL::default(); count as usize];
+		let count = unsafe { GetKeyboardLayoutList(Some(&mut layouts)) };
+		if count == 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(layouts
+			.into_iter()
+			.take(count as usize)
+			.map(Self::from)
+			.collect())
+	}
+
+	/// Loads a layout by its identifier, e.g. `"00000409"` for US English, whether or not the user has it installed.
+	///
+	/// It's loaded for mapping keys only, without becoming the thread's layout. A layout the user doesn't have
+	/// [`installed`](KeyboardLayout::installed) is added to the session's input languages though, until it's
+	/// [`unload`](KeyboardLayout::unload)ed.
+	///
+	/// Corresponds to [LoadKeyboardLayoutW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-loadkeyboardlayoutw).
+	#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+	pub fn load(id: &str) -> io::Result<Self> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let id: Vec<u16> = id.encode_utf16().chain([0]).collect();
+		let layout = unsafe { LoadKeyboardLayoutW(PCWSTR(id.as_ptr()), KLF_NOTELLSHELL) }?;
+		Ok(Self::from(layout))
+	}
+
+	/// Unloads a layout, removing it from the session's input languages, e.g. one [`load`](KeyboardLayout::load)ed that
+	/// the user didn't have [`installed`](KeyboardLayout::installed) before.
+	///
+	/// Corresponds to [UnloadKeyboardLayout](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unloadkeyboardlayout).
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn unload(self) -> io::Result<()> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		if unsafe { UnloadKeyboardLayout(HKL::from(self)) }.as_bool() {
+			Ok(())
+		} else {
+			Err(io::Error::last_os_error())
+		}
+	}
+
+	/// The layout's [language identifier](https://learn.microsoft.com/en-us/windows/win32/intl/language-identifiers),
+	/// e.g. `0x0409` for US English.
+	pub const fn language_id(self) -> u16 { self.0 as u16 }
+
+	/// The name of the layout's language, in the user interface's language, e.g. `"English (United States)"`, for
+	/// showing the layout to the user, such as in a list of the [`installed`](KeyboardLayout::installed) layouts.
+	///
+	/// Layouts of the same language with different keys, e.g. US and Dvorak, share the name.
+	///
+	/// Corresponds to [GetLocaleInfoW](https://learn.microsoft.com/en-us/windows/win32/api/winnls/nf-winnls-getlocaleinfow)
+	/// with `LOCALE_SLOCALIZEDDISPLAYNAME`.
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn language_name(self) -> io::Result<String> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		// a language identifier is the locale identifier of its default sort order
+		let locale = self.language_id() as u32;
+		let len = unsafe { GetLocaleInfoW(locale, LOCALE_SLOCALIZEDDISPLAYNAME, None) };
+		if len == 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let mut name = vec![0; len as usize];
+		let len = unsafe { GetLocaleInfoW(locale, LOCALE_SLOCALIZEDDISPLAYNAME, Some(&mut name)) };
+		if len == 0 {
+			return Err(io::Error::last_os_error());
+		}
+		// the length includes the terminating null
+		Ok(String::from_utf16_lossy(&name[..len as usize - 1]))
+	}
+}
+
+impl From<HKL> for KeyboardLayout {
+	fn from(value: HKL) -> Self { Self(value.0) }
+}
+
+impl From<KeyboardLayout> for HKL {
+	fn from(value: KeyboardLayout) -> Self { Self(value.0) }
+}
+
+/// The identifier a [`Hotkey`] is registered under, which tells which hotkey a [`HotkeyEvent::Hotkey`] is of.
+///
+/// Registered hotkeys of the same thread must have distinct identifiers, which [`next`](HotkeyId::next) hands out.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+#[repr(transparent)]
+pub struct HotkeyId(
+	/// The raw identifier.
+	pub i32,
+);
+
+impl HotkeyId {
+	/// Creates an identifier that no other call returned, within the process.
+	///
+	/// The identifiers are in the range RegisterHotKey allows applications (`0` to `0xBFFF`), wrapping around after
+	/// running out. They don't avoid identifiers chosen otherwise, e.g. with [`Hotkey::ID`], which is outside that range.
+	pub fn next() -> Self {
+		static NEXT: AtomicI32 = AtomicI32::new(0);
+		Self(NEXT.fetch_add(1, Ordering::Relaxed).rem_euclid(0xC000))
+	}
+}
+
+impl From<i32> for HotkeyId {
+	fn from(value: i32) -> Self { Self(value) }
+}
+
+impl From<HotkeyId> for i32 {
+	fn from(value: HotkeyId) -> Self { value.0 }
+}
+
+impl fmt::Display for HotkeyId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+}
+
 /// A global keyboard hotkey / shortcut that can be [`register`](Hotkey::register)ed.
+///
+/// With the `serde` feature, it serializes as a struct of its fields, and deserializes from either that or a string
+/// (see [`from_str`](Hotkey::from_str)), e.g. `"Ctrl+Alt+L"`.
 #[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(try_from = "HotkeyRepr")
+)]
 pub struct Hotkey {
 	/// The hotkey [`Modifiers`].
 	pub modifiers: Modifiers,
@@ -78,68 +600,889 @@ pub struct Hotkey {
 }
 
 impl Hotkey {
-	/// Registers the [`Hotkey`].
+	/// The identifier the hotkey is registered under by [`register`](Hotkey::register) and [`unregister`](Hotkey::unregister).
+	///
+	/// Identifiers are per thread, so if other code on the thread registers hotkeys under it too, pick another with
+	/// [`register_with_id`](Hotkey::register_with_id), or let a [`HotkeyManager`] pick them.
+	#[doc(alias = "DEFAULT_HOTKEY_ID")]
+	pub const ID: HotkeyId = HotkeyId(0x31710C4);
+
+	/// Creates a [`Hotkey`], e.g. `const LOCK: Hotkey = Hotkey::new(Modifiers::Control.union(Modifiers::Alt), keys::L);`.
+	pub const fn new(modifiers: Modifiers, key_code: Key) -> Self {
+		Self {
+			modifiers,
+			key_code,
+		}
+	}
+
+	/// Creates a [`Hotkey`] of the key that emits the character on the thread's current keyboard layout, [`None`] if none
+	/// does.
+	///
+	/// Only the given modifiers are added, even if the character requires others, so e.g. `'?'` makes a hotkey of the
+	/// <kbd>/</kbd> key on a US layout. See [`from_char_with_shift`](Hotkey::from_char_with_shift) to add them.
+	pub fn from_char(modifiers: Modifiers, c: char) -> Option<Self> {
+		Key::from_current_layout_char(c).map(|key_code| Self::new(modifiers, key_code))
+	}
+
+	/// Creates a [`Hotkey`] of the key that emits the character on the thread's current keyboard layout, with the
+	/// modifiers the character requires added to the given ones, [`None`] if no key emits it.
+	///
+	/// See [`Key::from_current_layout_char_with_mods`].
+	pub fn from_char_with_shift(modifiers: Modifiers, c: char) -> Option<Self> {
+		Key::from_current_layout_char_with_mods(c)
+			.map(|(key_code, key_modifiers)| Self::new(modifiers | key_modifiers, key_code))
+	}
+
+	/// Whether the hotkeys are pressed the same, i.e. are equal but for [`NoRepeat`](Modifiers::NoRepeat).
+	///
+	/// Unlike `==`, it suits checking whether a shortcut is already bound.
+	pub fn matches(&self, other: &Hotkey) -> bool {
+		self.key_code == other.key_code
+			&& self.modifiers - Modifiers::NoRepeat == other.modifiers - Modifiers::NoRepeat
+	}
+
+	/// Creates a [`HotkeyBuilder`], e.g. `Hotkey::builder().ctrl().alt().key(keys::L).build()`.
+	pub const fn builder() -> HotkeyBuilder { HotkeyBuilder::new() }
+
+	/// Registers the [`Hotkey`] under the default [`ID`](Hotkey::ID).
+	///
+	/// See [`register_with_id`](Hotkey::register_with_id).
+	pub fn register(self) -> Result<(), RegisterError> { self.register_with_id(Self::ID) }
+
+	/// Registers the [`Hotkey`] under the given identifier.
 	///
 	/// This procedure just has the system notify the application when the hotkey is pressed with a message.
-	/// Actually handling it is done in the message loop (see [`handle_event`]).
-	pub fn register(self) -> io::Result<()> {
-		let success = unsafe {
-			RegisterHotKey(
-				HWND::default(),
-				0x31710C4,
-				self.modifiers.into(),
-				self.key_code.0,
-			)
-			.as_bool()
-		};
-		if success {
+	/// Actually handling it is done in the message loop (see [`await_event`]).
+	///
+	/// Identifiers must be unique per thread: registering two hotkeys under the same identifier on the same thread fails.
+	///
+	/// Corresponds to [RegisterHotKey](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey).
+	#[doc(alias = "register_id")]
+	pub fn register_with_id(self, id: HotkeyId) -> Result<(), RegisterError> {
+		self.register_with_api(id, &SystemApi)
+	}
+
+	/// Registers the [`Hotkey`] under the given identifier, returning a [`Registration`] that unregisters it when dropped.
+	///
+	/// See [`register_with_id`](Hotkey::register_with_id).
+	pub fn register_owned(self, id: HotkeyId) -> Result<Registration, RegisterError> {
+		self.register_with_id(id)?;
+		Ok(Registration::new(id, self))
+	}
+
+	/// Registers the [`Hotkey`] under the given identifier through the given [`WinApi`].
+	///
+	/// See [`register_with_id`](Hotkey::register_with_id).
+	pub fn register_with_api(self, id: HotkeyId, api: &impl WinApi) -> Result<(), RegisterError> {
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!(
+			"register",
+			modifiers = ?self.modifiers,
+			key = self.key_code.0,
+			id = id.0
+		)
+		.entered();
+		self.check_modifiers()?;
+		let result = api.register_hotkey(id, self);
+		#[cfg(feature = "tracing")]
+		match &result {
+			Ok(()) => tracing::debug!("registered"),
+			Err(e) => tracing::debug!("failed to register: {e}"),
+		}
+		Ok(result?)
+	}
+
+	/// Registers the [`Hotkey`] under the first of the identifiers it registers under, returning that identifier.
+	///
+	/// It moves on to the next identifier when registering fails with [`AlreadyRegistered`](RegisterError::AlreadyRegistered),
+	/// and fails with it if none is left. Since that's also the error of another application having registered the
+	/// combination, every identifier fails in that case. Other errors return right away.
+	pub fn register_first_available(
+		self,
+		ids: impl IntoIterator<Item = HotkeyId>,
+	) -> Result<HotkeyId, RegisterError> {
+		self.register_first_available_with_api(ids, &SystemApi)
+	}
+
+	/// Registers the [`Hotkey`] under the first of the identifiers it registers under through the given [`WinApi`].
+	///
+	/// See [`register_first_available`](Hotkey::register_first_available).
+	pub fn register_first_available_with_api(
+		self,
+		ids: impl IntoIterator<Item = HotkeyId>,
+		api: &impl WinApi,
+	) -> Result<HotkeyId, RegisterError> {
+		for id in ids {
+			match self.register_with_api(id, api) {
+				Ok(()) => return Ok(id),
+				Err(RegisterError::AlreadyRegistered) => {}
+				Err(e) => return Err(e),
+			}
+		}
+		Err(RegisterError::AlreadyRegistered)
+	}
+
+	/// Checks whether the hotkey can be registered, i.e. no application (including this one) has registered it, by
+	/// registering it under a fresh identifier and unregistering it right away.
+	///
+	/// It fails on errors other than the hotkey being registered already, e.g. [`InvalidModifiers`](RegisterError::InvalidModifiers).
+	/// The answer can be outdated by the time it's acted on, if another application registers the hotkey in between.
+	pub fn is_available(self) -> Result<bool, RegisterError> {
+		self.is_available_with_api(&SystemApi)
+	}
+
+	/// Checks whether the hotkey can be registered through the given [`WinApi`].
+	///
+	/// See [`is_available`](Hotkey::is_available).
+	pub fn is_available_with_api(self, api: &impl WinApi) -> Result<bool, RegisterError> {
+		let id = HotkeyId::next();
+		match self.register_with_api(id, api) {
+			Ok(()) => {
+				self.unregister_with_api(id, api)?;
+				Ok(true)
+			}
+			Err(RegisterError::AlreadyRegistered) => Ok(false),
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Checks that the hotkey leaves its key usable: that it has a modifier, unless its key is one keyboards rarely
+	/// have (<kbd>F13</kbd> to <kbd>F24</kbd>) or a media, browser, or launch key, e.g. [`keys::VOLUME_UP`].
+	///
+	/// Registering a bare key as a hotkey takes the key over system-wide, which is rarely intended. The check is
+	/// advisory; [`register`](Hotkey::register) doesn't enforce it.
+	pub fn validate(self) -> Result<(), InvalidHotkey> {
+		if (self.modifiers - Modifiers::NoRepeat).is_empty()
+			&& !(keys::F13.0..=keys::F24.0).contains(&self.key_code.0)
+			&& !(keys::BROWSER_BACK.0..=keys::LAUNCH_APP2.0).contains(&self.key_code.0)
+		{
+			Err(InvalidHotkey::NoModifiers)
+		} else {
 			Ok(())
+		}
+	}
+
+	/// Fails with [`InvalidModifiers`](RegisterError::InvalidModifiers) if the modifiers have bits set that aren't any of
+	/// the [`Modifiers`], which every way of registering checks first.
+	pub(crate) fn check_modifiers(self) -> Result<(), RegisterError> {
+		if Modifiers::all().contains(self.modifiers) {
+			Ok(())
+		} else {
+			Err(RegisterError::InvalidModifiers)
+		}
+	}
+
+	/// Unregisters the [`Hotkey`], previously [`register`](Hotkey::register)ed.
+	pub fn unregister(self) -> io::Result<()> { self.unregister_with_id(Self::ID) }
+
+	/// Unregisters the hotkey registered under the given identifier, previously [`register_with_id`](Hotkey::register_with_id)ed.
+	///
+	/// Corresponds to [UnregisterHotKey](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey).
+	pub fn unregister_with_id(self, id: HotkeyId) -> io::Result<()> {
+		self.unregister_with_api(id, &SystemApi)
+	}
+
+	/// Unregisters the hotkey registered under the given identifier through the given [`WinApi`].
+	///
+	/// See [`unregister_with_id`](Hotkey::unregister_with_id).
+	pub fn unregister_with_api(self, id: HotkeyId, api: &impl WinApi) -> io::Result<()> {
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!("unregister", id = id.0).entered();
+		let result = api.unregister_hotkey(id);
+		#[cfg(feature = "tracing")]
+		if let Err(e) = &result {
+			tracing::debug!("failed to unregister: {e}");
+		}
+		result
+	}
+
+	/// Registers the [`Hotkey`] to a low-level keyboard hook under the default [`ID`](Hotkey::ID).
+	///
+	/// See [`register_hook_with_id`](Hotkey::register_hook_with_id).
+	pub fn register_hook(self, suppress: bool) -> Result<(), RegisterError> {
+		self.register_hook_with_id(Self::ID, suppress)
+	}
+
+	/// Registers the [`Hotkey`] to a low-level keyboard hook under the given identifier.
+	///
+	/// An alternative to [`register_with_id`](Hotkey::register_with_id) for hotkeys it rejects, like ones whose key is
+	/// <kbd>Caps Lock</kbd> or a modifier key, or ones the system reserves. Presses are reported the same way, as
+	/// [`HotkeyEvent::Hotkey`]s, and the hooked hotkeys matching a press all trigger. The modifiers must match exactly
+	/// (see [`is_triggered_by`](Hotkey::is_triggered_by)). Only single presses are matched, not sequences like double-taps
+	/// or chords of several non-modifier keys.
+	///
+	/// The hook is installed with the first hooked hotkey of the thread and uninstalled with the last. It's called by the
+	/// thread's message loop (see [`await_event`]), which should keep up since the system skips hooks that time out.
+	///
+	/// If `suppress` is set, the key events that trigger the hotkey, including its repeats and release, don't reach other
+	/// applications, so e.g. a single-key hotkey doesn't also type into the focused window.
+	///
+	/// Identifiers must be unique per thread among hooked hotkeys, but don't clash with [`register_with_id`](Hotkey::register_with_id)'s.
+	///
+	/// Corresponds to [SetWindowsHookExW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowshookexw)
+	/// with `WH_KEYBOARD_LL`.
+	pub fn register_hook_with_id(self, id: HotkeyId, suppress: bool) -> Result<(), RegisterError> {
+		hook::register(self, id, suppress, false)
+	}
+
+	/// Registers the [`Hotkey`] to a low-level keyboard hook under the given identifier, reporting both its presses and
+	/// its releases, as [`HotkeyEvent::Released`]s.
+	///
+	/// A release is reported once the hotkey's key is let go after a press, whether or not the modifiers still are, e.g.
+	/// to act when an operator stops holding a button. See [`register_hook_with_id`](Hotkey::register_hook_with_id).
+	pub fn register_hook_with_release(
+		self,
+		id: HotkeyId,
+		suppress: bool,
+	) -> Result<(), RegisterError> {
+		hook::register(self, id, suppress, true)
+	}
+
+	/// Whether a press of the key while the modifiers are held triggers the hotkey, when hooked.
+	///
+	/// The modifiers must be the hotkey's exactly, other than [`NoRepeat`](Modifiers::NoRepeat) and the key's own
+	/// [`modifier`](Key::modifier), which its repeats hold down, so e.g. <kbd>Left Win</kbd> alone is a hotkey.
+	pub fn is_triggered_by(self, key: Key, held: Modifiers) -> bool {
+		let own = key.modifier().unwrap_or(Modifiers::empty()) | Modifiers::NoRepeat;
+		self.key_code == key && self.modifiers - own == held - own
+	}
+
+	/// Unregisters the [`Hotkey`], previously [`register_hook`](Hotkey::register_hook)ed.
+	pub fn unregister_hook(self) -> io::Result<()> { self.unregister_hook_with_id(Self::ID) }
+
+	/// Unregisters the hooked hotkey registered under the given identifier, previously
+	/// [`register_hook_with_id`](Hotkey::register_hook_with_id)ed.
+	pub fn unregister_hook_with_id(self, id: HotkeyId) -> io::Result<()> { hook::unregister(id) }
+}
+
+impl FromStr for Hotkey {
+	type Err = ParseHotkeyError;
+
+	/// Parses a hotkey from `+`-separated modifiers followed by a key, case-insensitively, e.g. `Ctrl+Shift+L`.
+	///
+	/// A single-character key is resolved with [`Key::from_current_layout_char`], and a longer one with [`Key::from_name`]
+	/// or as a virtual key code in the `VK(0x4C)` form.
+	/// This is the inverse of [`Hotkey`]'s [`Display`](fmt::Display), except for [`NoRepeat`](Modifiers::NoRepeat).
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		let (modifiers, key) = match s.strip_suffix("++") {
+			Some(modifiers) => (modifiers, "+"),
+			None => s.rsplit_once('+').unwrap_or(("", s)),
+		};
+		let modifiers = Modifiers::from_names(
+			modifiers
+				.split('+')
+				.map(str::trim)
+				.filter(|name| !name.is_empty()),
+		)?;
+		let key = key.trim();
+		let mut chars = key.chars();
+		let key_code = match (chars.next(), chars.next()) {
+			(None, _) => return Err(ParseHotkeyError::MissingKey),
+			(Some(c), None) => Key::from_current_layout_char(c).or_else(|| Key::from_name(key)),
+			(Some(_), Some(_)) => Key::from_name(key).or_else(|| Key::parse_vk(key)),
+		}
+		.ok_or_else(|| ParseHotkeyError::UnknownKey(key.to_owned()))?;
+		Ok(Self {
+			modifiers,
+			key_code,
+		})
+	}
+}
+
+impl TryFrom<&str> for Hotkey {
+	type Error = ParseHotkeyError;
+
+	/// Parses the hotkey with its [`FromStr`].
+	fn try_from(value: &str) -> Result<Self, Self::Error> { value.parse() }
+}
+
+impl fmt::Display for Hotkey {
+	/// Writes the hotkey as `+`-separated modifiers followed by the key, e.g. `Ctrl+Alt+L`.
+	///
+	/// See [`Modifiers`]'s and [`Key`]'s [`Display`](fmt::Display).
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if (self.modifiers - Modifiers::NoRepeat).is_empty() {
+			write!(f, "{}", self.key_code)
 		} else {
-			Err(std::io::Error::last_os_error())
+			write!(f, "{}+{}", self.modifiers, self.key_code)
 		}
 	}
 }
 
+/// An error [`register`](Hotkey::register)ing a [`Hotkey`].
+///
+/// Registration used to fail with an [`io::Error`], which this converts into, so `?` keeps working in functions returning
+/// [`io::Result`].
+#[derive(Debug)]
+pub enum RegisterError {
+	/// The hotkey is already registered, by this or another application.
+	AlreadyRegistered,
+	/// The modifiers have bits set that aren't any of the [`Modifiers`].
+	InvalidModifiers,
+	/// Any other error from the system.
+	Os(io::Error),
+}
+
+impl From<io::Error> for RegisterError {
+	fn from(value: io::Error) -> Self {
+		match value.raw_os_error() {
+			Some(code) if code == ERROR_HOTKEY_ALREADY_REGISTERED.0 as i32 => {
+				Self::AlreadyRegistered
+			}
+			_ => Self::Os(value),
+		}
+	}
+}
+
+impl From<RegisterError> for io::Error {
+	fn from(value: RegisterError) -> Self {
+		match value {
+			RegisterError::AlreadyRegistered => {
+				io::Error::from_raw_os_error(ERROR_HOTKEY_ALREADY_REGISTERED.0 as i32)
+			}
+			RegisterError::InvalidModifiers => io::Error::new(io::ErrorKind::InvalidInput, value),
+			RegisterError::Os(e) => e,
+		}
+	}
+}
+
+impl fmt::Display for RegisterError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::AlreadyRegistered => f.write_str("the hotkey is already registered"),
+			Self::InvalidModifiers => f.write_str("the hotkey has invalid modifiers"),
+			Self::Os(e) => e.fmt(f),
+		}
+	}
+}
+
+impl Error for RegisterError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			Self::Os(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+/// The forms a [`Hotkey`] deserializes from.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum HotkeyRepr {
+	String(String),
+	Struct {
+		#[serde(default)]
+		modifiers: Modifiers,
+		key_code:  Key,
+	},
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<HotkeyRepr> for Hotkey {
+	type Error = ParseHotkeyError;
+
+	fn try_from(value: HotkeyRepr) -> Result<Self, Self::Error> {
+		match value {
+			HotkeyRepr::String(s) => s.parse(),
+			HotkeyRepr::Struct {
+				modifiers,
+				key_code,
+			} => Ok(Self {
+				modifiers,
+				key_code,
+			}),
+		}
+	}
+}
+
+/// A hotkey that fails [`Hotkey::validate`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum InvalidHotkey {
+	/// The hotkey has no modifiers, so its key can't be used normally while it's registered.
+	NoModifiers,
+}
+
+impl fmt::Display for InvalidHotkey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NoModifiers => {
+				f.write_str("the hotkey has no modifiers, so it takes its key over")
+			}
+		}
+	}
+}
+
+impl Error for InvalidHotkey {}
+
+/// An error parsing a [`Hotkey`] from a string.
+#[derive(Debug, Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
+pub enum ParseHotkeyError {
+	/// A token preceding the key isn't a known modifier.
+	UnknownModifier(String),
+	/// The key token doesn't map to a key.
+	UnknownKey(String),
+	/// The hotkey has no key.
+	MissingKey,
+}
+
+impl fmt::Display for ParseHotkeyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnknownModifier(name) => write!(f, "unknown modifier \"{name}\""),
+			Self::UnknownKey(name) => write!(f, "unknown key \"{name}\""),
+			Self::MissingKey => f.write_str("the hotkey is missing a key"),
+		}
+	}
+}
+
+impl Error for ParseHotkeyError {}
+
 /// An event from the Windows message loop in the context of hotkeys.
 #[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
 pub enum HotkeyEvent {
-	/// The hotkey was pressed.
-	Hotkey,
+	/// A hotkey was pressed.
+	Hotkey {
+		/// The identifier the pressed hotkey was registered under (see [`Hotkey::register_with_id`]).
+		id: HotkeyId,
+	},
+	/// A hotkey was released, if it was [`register_hook_with_release`](Hotkey::register_hook_with_release)ed.
+	Released {
+		/// The identifier the released hotkey was registered under.
+		id: HotkeyId,
+	},
+	/// A message [`post_user_message`] posted, with its payload.
+	User(usize),
+	/// The session's state changed, if subscribed to with [`SessionNotifications`].
+	Session(SessionEvent),
+	/// The system's power state changed, if subscribed to with [`PowerNotifications`].
+	Power(PowerEvent),
 	/// An irrelevant event occurred.
 	Other,
 	/// Got a quit signal.
 	Quit,
 }
 
+/// The lowest message the message loop receives.
+const MESSAGE_FILTER_MIN: u32 = WM_POWERBROADCAST;
+/// The highest message the message loop receives.
+const MESSAGE_FILTER_MAX: u32 = WM_UNREGISTER_HOTKEY;
+/// The message the hook posts when a hooked hotkey is released, with the same parameters as its `WM_HOTKEY`.
+const WM_HOTKEY_RELEASED: u32 = WM_APP + 0x3171;
+/// The message [`post_user_message`] posts, with the payload as its `wParam`.
+const WM_USER_EVENT: u32 = WM_APP + 0x3170;
+/// The message a [`Registration`] dropped on another thread posts to its thread, with its identifier as its `wParam`.
+const WM_UNREGISTER_HOTKEY: u32 = WM_APP + 0x3172;
+
 /// Blocks until the next Windows message.
-pub fn await_event() -> io::Result<HotkeyEvent> {
+///
+/// It takes the messages of every [`HotkeyEvent`], from `WM_POWERBROADCAST` to the crate's `WM_APP` messages (and
+/// `WM_QUIT`, which Windows delivers regardless), and leaves others queued. See [`await_event_filtered`] for other ranges.
+///
+/// Presses of every hotkey the thread registered are reported, whatever their identifiers, since other code on the
+/// thread may register hotkeys of its own to handle. To act on only some, e.g. the default [`Hotkey::ID`], see
+/// [`await_event_for`].
+pub fn await_event() -> io::Result<HotkeyEvent> { await_event_with(&SystemApi) }
+
+/// Blocks until the next Windows message, like [`await_event`], but with the presses and releases of hotkeys registered
+/// under identifiers other than the given ones as [`Other`](HotkeyEvent::Other).
+///
+/// See [`HotkeyEvent::only_among`].
+pub fn await_event_for(ids: &[HotkeyId]) -> io::Result<HotkeyEvent> {
+	Ok(await_event()?.only_among(ids))
+}
+
+/// Blocks until the next Windows message within the inclusive range of message identifiers, e.g. `WM_HOTKEY, WM_HOTKEY`
+/// for only hotkey presses and quit signals.
+///
+/// `WM_QUIT` is taken whatever the range, and a `0, 0` range takes all messages, which the ones of no [`HotkeyEvent`]
+/// are [`Other`](HotkeyEvent::Other) of. Messages outside the range stay queued for a later call or another loop.
+///
+/// Corresponds to [GetMessageW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub fn await_event_filtered(min: u32, max: u32) -> io::Result<HotkeyEvent> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
 	let mut message = Default::default();
-	let message_result =
-		unsafe { GetMessageW(&mut message, HWND::default(), WM_HOTKEY, WM_HOTKEY) };
-	let message = match message_result.0 {
-		0 => return Ok(HotkeyEvent::Quit),
-		-1 => return Err(io::Error::last_os_error()),
-		_ => message,
+	match unsafe { GetMessageW(&mut message, HWND::default(), min, max) }.0 {
+		-1 => Err(io::Error::last_os_error()),
+		_ => Ok(HotkeyEvent::receive_with(
+			&SystemApi,
+			Message::from(&message),
+		)),
+	}
+}
+
+/// Blocks until the next Windows message of the given [`WinApi`].
+///
+/// See [`await_event`].
+pub fn await_event_with(api: &impl WinApi) -> io::Result<HotkeyEvent> {
+	Ok(HotkeyEvent::receive_with(api, api.get_message()?))
+}
+
+/// Blocks until the next Windows message, returning it as is rather than as a [`HotkeyEvent`].
+///
+/// It's for what [`HotkeyEvent`] doesn't tell, e.g. the [`pressed_hotkey`](Message::pressed_hotkey).
+/// Interpret it with [`HotkeyEvent::receive_with`] for the rest, which also acts on the messages of dropped [`Registration`]s.
+pub fn await_raw() -> io::Result<Message> { SystemApi.get_message() }
+
+/// Iterates over the Windows messages, blocking for each (see [`await_event`]).
+///
+/// The iteration ends at the first [`Quit`](HotkeyEvent::Quit), which isn't yielded itself.
+pub fn events() -> impl Iterator<Item = io::Result<HotkeyEvent>> {
+	let mut quit = false;
+	iter::from_fn(move || {
+		if quit {
+			return None;
+		}
+		match await_event() {
+			Ok(HotkeyEvent::Quit) => {
+				quit = true;
+				None
+			}
+			event => Some(event),
+		}
+	})
+}
+
+/// Blocks until the next Windows message, or until the timeout elapses in which case it returns [`None`].
+///
+/// A zero timeout checks for a pending message without blocking.
+/// Timeouts too long to express to Windows are waited in parts rather than turning into an infinite wait.
+///
+/// Corresponds to [MsgWaitForMultipleObjectsEx](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-msgwaitformultipleobjectsex)
+/// and [PeekMessageW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub fn await_event_timeout(timeout: Duration) -> io::Result<Option<HotkeyEvent>> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let deadline = Instant::now().checked_add(timeout);
+	loop {
+		if let Some(event) = poll_event()? {
+			return Ok(Some(event));
+		}
+		let remaining = deadline.map_or(timeout, |deadline| {
+			deadline.saturating_duration_since(Instant::now())
+		});
+		if remaining.is_zero() {
+			return Ok(None);
+		}
+		let milliseconds = remaining.as_millis().min((INFINITE - 1) as u128) as u32;
+		let wait_result = unsafe {
+			MsgWaitForMultipleObjectsEx(
+				None,
+				milliseconds,
+				QS_HOTKEY | QS_POSTMESSAGE | QS_SENDMESSAGE,
+				MSG_WAIT_FOR_MULTIPLE_OBJECTS_EX_FLAGS::default(),
+			)
+		};
+		if WIN32_ERROR(wait_result) == WAIT_FAILED {
+			return Err(io::Error::last_os_error());
+		}
+	}
+}
+
+/// Takes the next pending Windows message without blocking, or [`None`] if there isn't any.
+///
+/// Messages are interpreted the same as in [`await_event`].
+///
+/// Corresponds to [PeekMessageW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew).
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn poll_event() -> io::Result<Option<HotkeyEvent>> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let mut message = Default::default();
+	let available = unsafe {
+		PeekMessageW(
+			&mut message,
+			HWND::default(),
+			MESSAGE_FILTER_MIN,
+			MESSAGE_FILTER_MAX,
+			PM_REMOVE,
+		)
+		.as_bool()
 	};
-	match message.message {
-		WM_HOTKEY => Ok(HotkeyEvent::Hotkey),
-		WM_QUIT => Ok(HotkeyEvent::Quit),
-		_ => Ok(HotkeyEvent::Quit),
+	Ok(available.then(|| HotkeyEvent::receive_with(&SystemApi, Message::from(&message))))
+}
+
+/// Discards the pending hotkey presses, returning how many there were.
+///
+/// Presses made while a slow handler runs queue up, e.g. during [`RELOCK_DELAY`], and would otherwise be handled one after
+/// another once it returns. Calling it after handling a press coalesces such a burst into the one press handled.
+/// Messages other than hotkey presses stay queued.
+///
+/// Corresponds to [PeekMessageW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew)
+/// with `WM_HOTKEY`.
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn drain_events() -> usize {
+	#[cfg(not(windows))]
+	return 0;
+	let mut message = Default::default();
+	let mut drained = 0;
+	while unsafe {
+		PeekMessageW(
+			&mut message,
+			HWND::default(),
+			WM_HOTKEY,
+			WM_HOTKEY,
+			PM_REMOVE,
+		)
+	}
+	.as_bool()
+	{
+		drained += 1;
+	}
+	drained
+}
+
+/// Posts a quit signal to the thread's message queue, which its message loop receives as [`HotkeyEvent::Quit`].
+///
+/// This stops a message loop from another thread, e.g. one running a [`HotkeyManager`] in the background.
+/// See [`current_thread_id`] for getting the identifier on the thread of the loop.
+///
+/// Corresponds to [PostThreadMessageW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postthreadmessagew)
+/// with `WM_QUIT`.
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub fn post_quit(thread_id: u32) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(unsupported());
+	let success = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) }.as_bool();
+	if success {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// Posts a message with the payload to the thread's message queue, which its message loop receives as
+/// [`HotkeyEvent::User`].
+///
+/// This wakes a message loop from another thread to have it do work of the payload's meaning between handling hotkeys.
+///
+/// Corresponds to [PostThreadMessageW](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postthreadmessagew)
+/// with a `WM_APP` message.
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub fn post_user_message(thread_id: u32, payload: usize) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(unsupported());
+	let success =
+		unsafe { PostThreadMessageW(thread_id, WM_USER_EVENT, WPARAM(payload), LPARAM(0)) }
+			.as_bool();
+	if success {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// The Windows identifier of the calling thread (see [`post_quit`]).
+///
+/// Corresponds to [GetCurrentThreadId](https://learn.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-getcurrentthreadid).
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn current_thread_id() -> u32 {
+	#[cfg(not(windows))]
+	return 0;
+	unsafe { GetCurrentThreadId() }
+}
+
+impl HotkeyEvent {
+	/// Interprets a message received from the message queue.
+	///
+	/// The message of a [`Registration`] dropped on another thread is interpreted as [`Other`](HotkeyEvent::Other),
+	/// without acting on it (see [`receive_with`](HotkeyEvent::receive_with)).
+	pub fn from_message(message: Message) -> Self {
+		let event = match message.message {
+			WM_HOTKEY => HotkeyEvent::Hotkey {
+				id: HotkeyId(message.wparam as i32),
+			},
+			WM_HOTKEY_RELEASED => HotkeyEvent::Released {
+				id: HotkeyId(message.wparam as i32),
+			},
+			WM_USER_EVENT => HotkeyEvent::User(message.wparam),
+			WM_WTSSESSION_CHANGE => match message.wparam as u32 {
+				WTS_SESSION_LOCK => HotkeyEvent::Session(SessionEvent::Lock),
+				WTS_SESSION_UNLOCK => HotkeyEvent::Session(SessionEvent::Unlock),
+				_ => HotkeyEvent::Other,
+			},
+			WM_POWERBROADCAST => match message.wparam as u32 {
+				PBT_APMSUSPEND => HotkeyEvent::Power(PowerEvent::Suspend),
+				PBT_APMRESUMEAUTOMATIC => HotkeyEvent::Power(PowerEvent::Resume),
+				_ => HotkeyEvent::Other,
+			},
+			WM_QUIT => HotkeyEvent::Quit,
+			_ => HotkeyEvent::Other,
+		};
+		#[cfg(feature = "tracing")]
+		tracing::trace!(
+			message = message.message,
+			wparam = message.wparam,
+			lparam = message.lparam,
+			?event,
+			"received a message"
+		);
+		event
+	}
+
+	/// Interprets a message received from the message queue of the given [`WinApi`], like
+	/// [`from_message`](HotkeyEvent::from_message), and acts on it.
+	///
+	/// The message of a [`Registration`] dropped on another thread has its hotkey unregistered through the API, which is
+	/// how [`await_event`] and the other waiting functions release such registrations.
+	pub fn receive_with(api: &impl WinApi, message: Message) -> Self {
+		if message.message == WM_UNREGISTER_HOTKEY {
+			let _ = api.unregister_hotkey(HotkeyId(message.wparam as i32));
+		}
+		HotkeyEvent::from_message(message)
+	}
+
+	/// The event, but with presses and releases of hotkeys registered under other identifiers as
+	/// [`Other`](HotkeyEvent::Other), e.g. to have only the default [`Hotkey::ID`] trigger an action while other code on
+	/// the thread registers hotkeys too.
+	///
+	/// A [`HotkeyManager`] does the same for the identifiers it tracks.
+	pub fn only_for(self, id: HotkeyId) -> Self { self.only_among(&[id]) }
+
+	/// The event, but with presses and releases of hotkeys registered under identifiers other than the given ones as
+	/// [`Other`](HotkeyEvent::Other).
+	///
+	/// See [`only_for`](HotkeyEvent::only_for).
+	pub fn only_among(self, ids: &[HotkeyId]) -> Self {
+		match self {
+			HotkeyEvent::Hotkey { id: pressed } | HotkeyEvent::Released { id: pressed }
+				if !ids.contains(&pressed) =>
+			{
+				HotkeyEvent::Other
+			}
+			event => event,
+		}
 	}
 }
 
+/// The error of the procedures on targets other than Windows, with the `stub` feature.
+#[cfg(not(windows))]
+fn unsupported() -> io::Error {
+	io::Error::new(io::ErrorKind::Unsupported, "winlock only supports Windows")
+}
+
 /// Locks the workstation / user session.
 ///
 /// This procedure can return a successful result but not have the workstation locked. This can happen for details specified in the
-/// Windows API documentation linked below, or because [workstation locking is disabled](set_lock_enabled).
+/// Windows API documentation linked below, or because [workstation locking is disabled](set_lock_enabled), which
+/// [`lock_workstation_checked`] tells apart. [`lock_and_wait`] waits for the lock to take effect instead.
+///
+/// It fails with [`AlreadyLocked`](LockError::AlreadyLocked) if the workstation fails to lock because it's locked already,
+/// which callers can usually treat as success.
 ///
 /// Corresponds to [LockWorkStation](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-lockworkstation).
-pub fn lock_workstation() -> io::Result<()> {
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn lock_workstation() -> Result<(), LockError> {
+	#[cfg(not(windows))]
+	return Err(LockError::Os(crate::unsupported()));
 	let result = unsafe { windows::Win32::System::Shutdown::LockWorkStation() }.as_bool();
 	if result {
 		Ok(())
 	} else {
-		Err(io::Error::last_os_error())
+		let error = io::Error::last_os_error();
+		// the error Windows reports for a locked session isn't documented, so the session is asked instead
+		if is_workstation_locked().unwrap_or(false) {
+			Err(LockError::AlreadyLocked)
+		} else {
+			Err(LockError::Os(error))
+		}
+	}
+}
+
+/// An error [`lock_workstation`]ing.
+///
+/// Locking used to fail with an [`io::Error`], which this converts into, so `?` keeps working in functions returning
+/// [`io::Result`].
+#[derive(Debug)]
+pub enum LockError {
+	/// The workstation is locked already.
+	AlreadyLocked,
+	/// Any other error from the system.
+	Os(io::Error),
+}
+
+impl From<LockError> for io::Error {
+	fn from(value: LockError) -> Self {
+		match value {
+			LockError::AlreadyLocked => io::Error::other(value),
+			LockError::Os(e) => e,
+		}
+	}
+}
+
+impl fmt::Display for LockError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::AlreadyLocked => f.write_str("the workstation is already locked"),
+			Self::Os(e) => e.fmt(f),
+		}
+	}
+}
+
+impl Error for LockError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			Self::Os(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+/// Whether a lock [`lock_workstation_checked`] issued is likely to take effect.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum LockOutcome {
+	/// The lock was issued and locking is enabled, so the workstation is likely locking.
+	Issued,
+	/// The lock was issued but locking is disabled by the user's or the machine's policy, so it's likely a no-op.
+	Suppressed,
+}
+
+/// Locks the workstation like [`lock_workstation`], then checks the locking policy to tell whether the lock was likely
+/// suppressed.
+///
+/// The lock takes effect asynchronously, so the outcome is a best guess: a policy that fails to read counts as enabling
+/// locking, and the policy changing right after issuing the lock isn't accounted for.
+pub fn lock_workstation_checked() -> Result<LockOutcome, LockError> {
+	lock_workstation()?;
+	let enabled = [PolicyScope::User, PolicyScope::Machine]
+		.into_iter()
+		.all(|scope| get_lock_enabled_in(scope).unwrap_or(true));
+	Ok(if enabled {
+		LockOutcome::Issued
+	} else {
+		LockOutcome::Suppressed
+	})
+}
+
+/// The registry key holding the workstation locking policy, under the [`PolicyScope`]'s root.
+const LOCK_POLICY_KEY: PCWSTR = w!(r"Software\Microsoft\Windows\CurrentVersion\Policies\System");
+/// The registry value of the workstation locking policy, under [`LOCK_POLICY_KEY`].
+const LOCK_POLICY_VALUE: PCWSTR = w!(r"DisableLockWorkstation");
+
+/// Where a workstation locking policy is stored.
+#[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum PolicyScope {
+	/// The current user's policy, under `HKEY_CURRENT_USER`.
+	#[default]
+	User,
+	/// The policy of all users, under `HKEY_LOCAL_MACHINE`.
+	///
+	/// Some machines, e.g. domain-joined ones, ignore the user's policy in favor of this one.
+	/// Writing it requires elevation, and group policy may overwrite it.
+	Machine,
+}
+
+impl PolicyScope {
+	/// The registry root of the scope.
+	fn root(self) -> HKEY {
+		match self {
+			PolicyScope::User => HKEY_CURRENT_USER,
+			PolicyScope::Machine => HKEY_LOCAL_MACHINE,
+		}
 	}
 }
 
@@ -150,21 +1493,252 @@ pub fn lock_workstation() -> io::Result<()> {
 /// therefore preventing the computer from locking altogether.
 ///
 /// This procedure achieves its behavior by modifying the Windows registry so expect this to only work with elevated privileges.
+/// Without them, it fails with a [`NotElevated`] error.
+///
+/// Writes that fail because another process holds the registry, which happens e.g. right after boot
+/// (`ERROR_SHARING_VIOLATION`, `ERROR_LOCK_VIOLATION`, and `ERROR_BUSY`), are retried a few times with a growing wait.
+/// Other errors fail immediately.
+///
+/// It sets the current user's policy, see [`set_lock_enabled_in`] for the machine's.
+///
+/// See also [`get_lock_enabled`] and [`disable_lock_scoped`].
 pub fn set_lock_enabled(enabled: bool) -> io::Result<()> {
-	let data: u32 = if enabled { 0 } else { 1 };
-	let result = unsafe {
-		RegSetKeyValueW(
-			HKEY_CURRENT_USER,
-			w!(r"Software\Microsoft\Windows\CurrentVersion\Policies\System"),
-			w!(r"DisableLockWorkstation"),
-			REG_DWORD.0,
-			Some(&data as *const _ as *const _),
-			mem::size_of_val(&data) as _,
+	set_lock_enabled_with(&SystemApi, enabled)
+}
+
+/// Sets whether to enable or disable workstation locking in the given scope.
+///
+/// See [`set_lock_enabled`].
+pub fn set_lock_enabled_in(scope: PolicyScope, enabled: bool) -> io::Result<()> {
+	set_lock_policy(scope, Some(if enabled { 0 } else { 1 }))
+}
+
+/// Sets whether to enable or disable workstation locking through the given [`WinApi`].
+///
+/// See [`set_lock_enabled`].
+pub fn set_lock_enabled_with(api: &impl WinApi, enabled: bool) -> io::Result<()> {
+	api.reg_set_value(Some(if enabled { 0 } else { 1 }))
+}
+
+/// Gets whether workstation locking is enabled.
+///
+/// Locking is enabled unless the policy disables it, so an absent policy reads as enabled.
+///
+/// See [`set_lock_enabled`].
+pub fn get_lock_enabled() -> io::Result<bool> { get_lock_enabled_with(&SystemApi) }
+
+/// Gets whether workstation locking is enabled by the policy of the given scope.
+///
+/// See [`get_lock_enabled`].
+pub fn get_lock_enabled_in(scope: PolicyScope) -> io::Result<bool> {
+	Ok(lock_policy(scope)?.unwrap_or(0) == 0)
+}
+
+/// Gets whether workstation locking is enabled through the given [`WinApi`].
+///
+/// See [`get_lock_enabled`].
+pub fn get_lock_enabled_with(api: &impl WinApi) -> io::Result<bool> {
+	Ok(api.reg_get_value()?.unwrap_or(0) == 0)
+}
+
+/// A delay between [`lock_workstation`] and disabling locking that usually avoids the race described in [`set_lock_enabled`].
+///
+/// Slower machines may need longer. See [`lock_then_disable`] for waiting on the lock itself instead.
+pub const RELOCK_DELAY: Duration = Duration::from_millis(500);
+
+/// How long [`lock_then_disable`] waits for the workstation to lock.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often [`lock_and_wait`] checks whether the workstation locked.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Locks the workstation, then disables workstation locking once the session is locked.
+///
+/// Unlike disabling locking right after [`lock_workstation`], this doesn't race the lock (see [`set_lock_enabled`]),
+/// since it waits for the lock to take effect instead of for a fixed delay.
+/// Locking is enabled beforehand, as it must be for the workstation to lock.
+///
+/// If the workstation doesn't lock within a few seconds, it fails with [`io::ErrorKind::TimedOut`] and leaves locking enabled.
+pub fn lock_then_disable() -> io::Result<()> {
+	set_lock_enabled(true)?;
+	if !lock_and_wait(LOCK_TIMEOUT)? {
+		return Err(io::Error::new(
+			io::ErrorKind::TimedOut,
+			"the workstation didn't lock in time",
+		));
+	}
+	set_lock_enabled(false)
+}
+
+/// Locks the workstation like [`lock_workstation`], then waits up to the timeout for the session to actually be locked,
+/// returning whether it was, e.g. for a script that must not go on with the workstation unlocked.
+///
+/// It polls [`is_workstation_locked`], and fails where that does, e.g. on systems older than Windows 7.
+pub fn lock_and_wait(timeout: Duration) -> io::Result<bool> {
+	match lock_workstation() {
+		Ok(()) => {}
+		Err(LockError::AlreadyLocked) => return Ok(true),
+		Err(e) => return Err(e.into()),
+	}
+	let deadline = Instant::now().checked_add(timeout);
+	loop {
+		if is_workstation_locked()? {
+			return Ok(true);
+		}
+		let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+		if remaining.is_some_and(|remaining| remaining.is_zero()) {
+			return Ok(false);
+		}
+		thread::sleep(remaining.map_or(LOCK_POLL_INTERVAL, |remaining| {
+			remaining.min(LOCK_POLL_INTERVAL)
+		}));
+	}
+}
+
+/// Reads the raw `DisableLockWorkstation` registry value, [`None`] if absent.
+fn lock_policy(scope: PolicyScope) -> io::Result<Option<u32>> {
+	registry::get_dword(scope.root(), LOCK_POLICY_KEY, LOCK_POLICY_VALUE)
+}
+
+/// Writes the raw `DisableLockWorkstation` registry value, deleting it for [`None`].
+fn set_lock_policy(scope: PolicyScope, data: Option<u32>) -> io::Result<()> {
+	let result = registry::retry_transient(|| match data {
+		Some(data) => registry::set_dword(scope.root(), LOCK_POLICY_KEY, LOCK_POLICY_VALUE, data),
+		None => registry::delete_value(scope.root(), LOCK_POLICY_KEY, LOCK_POLICY_VALUE),
+	});
+	match result {
+		Err(e)
+			if e.raw_os_error() == Some(ERROR_ACCESS_DENIED.0 as _)
+				&& matches!(is_elevated(), Ok(false)) =>
+		{
+			Err(io::Error::new(io::ErrorKind::PermissionDenied, NotElevated))
+		}
+		result => result,
+	}
+}
+
+/// Gets whether the process runs elevated, i.e. as administrator, which changing the locking policy requires.
+///
+/// Corresponds to [GetTokenInformation](https://learn.microsoft.com/en-us/windows/win32/api/securitybaseapi/nf-securitybaseapi-gettokeninformation)
+/// with `TokenElevation`.
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn is_elevated() -> io::Result<bool> {
+	#[cfg(not(windows))]
+	return Err(unsupported());
+	let mut token = HANDLE::default();
+	if !unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }.as_bool() {
+		return Err(io::Error::last_os_error());
+	}
+	let mut elevation = TOKEN_ELEVATION::default();
+	let mut size = 0;
+	let success = unsafe {
+		GetTokenInformation(
+			token,
+			TokenElevation,
+			Some(&mut elevation as *mut _ as *mut _),
+			mem::size_of_val(&elevation) as _,
+			&mut size,
 		)
-	};
-	if result.is_ok() {
-		Ok(())
+	}
+	.as_bool();
+	let result = if success {
+		Ok(elevation.TokenIsElevated != 0)
 	} else {
-		Err(io::Error::from_raw_os_error(result.0 as _))
+		Err(io::Error::last_os_error())
+	};
+	unsafe { CloseHandle(token) };
+	result
+}
+
+/// The error of changing the locking policy without [elevation](is_elevated), as the [`io::Error::get_ref`] of a
+/// [`PermissionDenied`](io::ErrorKind::PermissionDenied) error.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct NotElevated;
+
+impl fmt::Display for NotElevated {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("changing the locking policy requires running as administrator")
+	}
+}
+
+impl Error for NotElevated {}
+
+/// The workstation locking policy at some point, to restore later.
+///
+/// Unlike [`set_lock_enabled`], restoring it reproduces the policy exactly, including a policy that disables locking and
+/// the absence of one.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub struct LockPolicySnapshot {
+	scope: PolicyScope,
+	value: Option<u32>,
+}
+
+impl LockPolicySnapshot {
+	/// Reads the current user's policy.
+	pub fn take() -> io::Result<Self> { Self::take_in(PolicyScope::User) }
+
+	/// Reads the policy of the given scope.
+	pub fn take_in(scope: PolicyScope) -> io::Result<Self> {
+		Ok(Self {
+			scope,
+			value: lock_policy(scope)?,
+		})
+	}
+
+	/// Reads the policy through the given [`WinApi`], whose registry value is the current user's policy.
+	pub fn take_with(api: &impl WinApi) -> io::Result<Self> {
+		Ok(Self {
+			scope: PolicyScope::User,
+			value: api.reg_get_value()?,
+		})
+	}
+
+	/// Writes the policy back to its scope, deleting it if there was none.
+	///
+	/// Like [`set_lock_enabled`], expect this to only work with elevated privileges.
+	pub fn restore(self) -> io::Result<()> { set_lock_policy(self.scope, self.value) }
+
+	/// Writes the policy back through the given [`WinApi`], deleting it if there was none.
+	pub fn restore_with(self, api: &impl WinApi) -> io::Result<()> { api.reg_set_value(self.value) }
+
+	/// Whether locking was enabled by the policy (see [`get_lock_enabled`]).
+	pub fn lock_enabled(self) -> bool { self.value.unwrap_or(0) == 0 }
+}
+
+/// Disables workstation locking until the returned guard is dropped, which restores the prior policy.
+///
+/// Prefer this to [`set_lock_enabled`] so the policy is restored even on early returns and panics.
+///
+/// Like [`set_lock_enabled`], expect this to only work with elevated privileges.
+pub fn disable_lock_scoped() -> io::Result<LockDisableGuard> { disable_lock_scoped_with(SystemApi) }
+
+/// Disables workstation locking through the given [`WinApi`] until the returned guard is dropped.
+///
+/// See [`disable_lock_scoped`].
+pub fn disable_lock_scoped_with<A: WinApi>(api: A) -> io::Result<LockDisableGuard<A>> {
+	let previous = LockPolicySnapshot::take_with(&api)?;
+	set_lock_enabled_with(&api, false)?;
+	Ok(LockDisableGuard { api, previous })
+}
+
+/// Restores the workstation locking policy that preceded [`disable_lock_scoped`] when dropped, including while
+/// unwinding from a panic.
+///
+/// Failing to restore the policy is logged with the `tracing` feature.
+#[derive(Debug)]
+#[must_use = "locking is restored as soon as the guard is dropped"]
+pub struct LockDisableGuard<A: WinApi = SystemApi> {
+	api:      A,
+	previous: LockPolicySnapshot,
+}
+
+impl<A: WinApi> Drop for LockDisableGuard<A> {
+	fn drop(&mut self) {
+		let result = self.previous.restore_with(&self.api);
+		#[cfg(feature = "tracing")]
+		if let Err(e) = result {
+			tracing::error!("failed to restore the workstation locking policy: {e}");
+		}
+		#[cfg(not(feature = "tracing"))]
+		let _ = result;
 	}
 }
@@ -0,0 +1,173 @@
+use std::{
+	io,
+	sync::mpsc::{self, Receiver},
+	thread::{self, JoinHandle},
+};
+
+use windows::Win32::{
+	Foundation::HWND,
+	UI::WindowsAndMessaging::{PeekMessageW, PM_NOREMOVE, WM_USER},
+};
+
+use crate::{Hotkey, HotkeyEvent, HotkeyId, HotkeyManager, ManagerEvent, RegisterError};
+
+/// A message loop running on a background thread, see [`spawn`].
+///
+/// Dropping it stops the loop, which unregisters the hotkeys.
+#[derive(Debug)]
+pub struct Listener {
+	pump:   Pump,
+	events: Receiver<io::Result<HotkeyEvent>>,
+}
+
+/// Spawns a thread that registers the hotkeys and runs the message loop, forwarding its events to the returned [`Listener`].
+///
+/// [`HotkeyEvent::Hotkey`] events identify the hotkeys by their index in the iteration, as a [`HotkeyId`]. The
+/// identifiers they're registered under on the spawned thread are internal, so they don't match [`Hotkey::ID`] or any
+/// chosen otherwise. Since hotkeys are delivered to the thread that registers them, they're registered on the spawned thread, and it
+/// returns only after they are, failing if any of them fails to register.
+///
+/// The events end at [`HotkeyEvent::Quit`], which isn't forwarded, e.g. after [`stop`](Listener::stop).
+pub fn spawn(hotkeys: impl IntoIterator<Item = Hotkey>) -> Result<Listener, RegisterError> {
+	let (sender, events) = mpsc::channel();
+	let pump = Pump::spawn(hotkeys, move |event| sender.send(event).is_ok())?;
+	Ok(Listener { pump, events })
+}
+
+impl Listener {
+	/// The Windows identifier of the listener's thread, e.g. for [`post_quit`](crate::post_quit).
+	pub fn thread_id(&self) -> u32 { self.pump.thread_id }
+
+	/// The events of the listener's message loop.
+	pub fn events(&self) -> &Receiver<io::Result<HotkeyEvent>> { &self.events }
+
+	/// Stops the message loop and waits for the thread to finish.
+	pub fn stop(mut self) -> io::Result<()> { self.pump.stop() }
+}
+
+/// A message loop running on a background thread whose events can be awaited, see [`AsyncListener::spawn`].
+///
+/// Dropping it stops the loop, which unregisters the hotkeys.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncListener {
+	pump:   Pump,
+	events: tokio::sync::mpsc::UnboundedReceiver<io::Result<HotkeyEvent>>,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncListener {
+	/// Spawns a thread that registers the hotkeys and runs the message loop, forwarding its events to the returned listener.
+	///
+	/// The message loop can't be awaited itself: hotkey messages are delivered to the thread that registered them, which
+	/// must block on them, so a dedicated thread pumps them rather than the async runtime's.
+	///
+	/// See [`spawn`] for the registration.
+	pub fn spawn(hotkeys: impl IntoIterator<Item = Hotkey>) -> Result<Self, RegisterError> {
+		let (sender, events) = tokio::sync::mpsc::unbounded_channel();
+		let pump = Pump::spawn(hotkeys, move |event| sender.send(event).is_ok())?;
+		Ok(Self { pump, events })
+	}
+
+	/// Waits for the next event of the message loop, or [`None`] once it has stopped.
+	pub async fn next_event(&mut self) -> Option<io::Result<HotkeyEvent>> {
+		self.events.recv().await
+	}
+
+	/// The Windows identifier of the listener's thread, e.g. for [`post_quit`](crate::post_quit).
+	pub fn thread_id(&self) -> u32 { self.pump.thread_id }
+
+	/// Stops the message loop and waits for the thread to finish.
+	pub fn stop(mut self) -> io::Result<()> { self.pump.stop() }
+}
+
+/// The thread of a listener, which it stops when dropped.
+#[derive(Debug)]
+struct Pump {
+	thread_id: u32,
+	thread:    Option<JoinHandle<()>>,
+}
+
+impl Pump {
+	/// Spawns the thread, which hands the events to `send` until it returns `false`.
+	#[cfg_attr(not(windows), allow(unreachable_code, unused_variables, unused_mut))]
+	fn spawn(
+		hotkeys: impl IntoIterator<Item = Hotkey>,
+		mut send: impl FnMut(io::Result<HotkeyEvent>) -> bool + Send + 'static,
+	) -> Result<Self, RegisterError> {
+		#[cfg(not(windows))]
+		return Err(RegisterError::Os(crate::unsupported()));
+		let hotkeys: Vec<Hotkey> = hotkeys.into_iter().collect();
+		let (setup_sender, setup) = mpsc::channel();
+		let thread = thread::spawn(move || {
+			let mut manager = HotkeyManager::new();
+			let registered = hotkeys
+				.into_iter()
+				.map(|hotkey| manager.register(hotkey))
+				.collect::<Result<Vec<_>, _>>();
+			// posting to the thread fails until it has a message queue, which peeking creates
+			let mut message = Default::default();
+			unsafe { PeekMessageW(&mut message, HWND::default(), WM_USER, WM_USER, PM_NOREMOVE) };
+			let ids = match registered {
+				Ok(ids) => {
+					let _ = setup_sender.send(Ok(crate::current_thread_id()));
+					ids
+				}
+				Err(e) => {
+					let _ = setup_sender.send(Err(e));
+					return;
+				}
+			};
+			loop {
+				let event = match manager.await_event() {
+					Ok(ManagerEvent::Quit) => break,
+					Ok(ManagerEvent::Hotkey { id, .. }) => {
+						let index = ids.iter().position(|&registered| registered == id);
+						match index {
+							Some(index) => Ok(HotkeyEvent::Hotkey {
+								id: HotkeyId(index as i32),
+							}),
+							None => continue,
+						}
+					}
+					Ok(ManagerEvent::User(payload)) => Ok(HotkeyEvent::User(payload)),
+					Ok(ManagerEvent::Session(event)) => Ok(HotkeyEvent::Session(event)),
+					Ok(ManagerEvent::Power(event)) => Ok(HotkeyEvent::Power(event)),
+					Ok(ManagerEvent::Other) => continue,
+					Err(e) => Err(e),
+				};
+				if !send(event) {
+					break;
+				}
+			}
+		});
+		let thread_id = match setup.recv() {
+			Ok(setup) => setup?,
+			Err(_) => {
+				return Err(RegisterError::Os(io::Error::other(
+					"the listener thread exited before registering the hotkeys",
+				)))
+			}
+		};
+		Ok(Self {
+			thread_id,
+			thread: Some(thread),
+		})
+	}
+
+	fn stop(&mut self) -> io::Result<()> {
+		let Some(thread) = self.thread.take() else {
+			return Ok(());
+		};
+		if !thread.is_finished() {
+			crate::post_quit(self.thread_id)?;
+		}
+		thread
+			.join()
+			.map_err(|_| io::Error::other("the listener thread panicked"))
+	}
+}
+
+impl Drop for Pump {
+	fn drop(&mut self) { let _ = self.stop(); }
+}
@@ -1,86 +1,446 @@
-use std::time::Duration;
+use std::{
+	collections::BTreeMap,
+	fs, io,
+	path::PathBuf,
+	str::FromStr,
+	time::{Duration, Instant},
+};
 
-use clap::Parser;
+use clap::{parser::ValueSource, CommandFactory, Parser};
 use tracing::metadata::LevelFilter;
 use tracing_subscriber::EnvFilter;
-use winlock::{HotkeyEvent, Key, Modifiers};
+use winlock::{
+	keys, Action, AuditLogger, CapturedKey, Debounce, Hotkey, HotkeyEvent, HotkeyId, HotkeyManager,
+	HotkeyOptions, Key, KeyboardLayout, LockError, LockOutcome, LockPolicySnapshot, Modifiers,
+	OptionsKeyError, PolicyScope, PowerEvent, PowerNotifications, RegisterError, SessionEvent,
+	SessionNotifications,
+};
 
-#[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, clap::Parser)]
+#[derive(Debug, Hash, Default, Clone, PartialEq, Eq, clap::Parser)]
+struct Cli {
+	#[command(flatten)]
+	options:             Options,
+	/// Read the options from a TOML file.
+	///
+	/// The file has the same options as the command line, named like the long flags (e.g. disable-windows = true),
+	/// plus a hotkey option (e.g. hotkey = "Ctrl+Alt+L").
+	/// Options given on the command line take precedence over the file's. It's an error for the file to be missing.
+	#[arg(long, value_name = "PATH")]
+	config:              Option<PathBuf>,
+	/// Apply a profile of the --config file, a named table of options under [profile.NAME] (e.g. [profile.meeting]).
+	///
+	/// The profile's options take precedence over the rest of the file's, and the command line's over both.
+	#[arg(long, value_name = "NAME", requires = "config")]
+	profile:             Option<String>,
+	/// Log only errors, regardless of WINLOCK_LOG.
+	///
+	/// Logs go to stderr, and results (e.g. of --print-resolved) to stdout.
+	#[arg(short, long)]
+	quiet:               bool,
+	/// Bind a hotkey to an action, e.g. "Ctrl+Alt+S=sleep". Can be repeated.
+	///
+	/// Actions: lock, sleep, hibernate, log-off, shutdown, restart, monitor-off, screensaver, disconnect, and toggle-lock,
+	/// which enables Windows locking if it's disabled and disables it otherwise (per --machine). A force- prefix skips
+	/// notifying applications for sleep, hibernate, log-off, shutdown, and restart, and exec:COMMAND runs the command,
+	/// e.g. "Ctrl+Alt+T=exec:wt".
+	#[arg(long, value_name = "HOTKEY=ACTION")]
+	bind:                Vec<Binding>,
+	/// Lock the workstation immediately and exit, instead of listening to hotkeys.
+	///
+	/// Honors -d and -r, and exits with a non-zero code if locking fails.
+	#[arg(long, conflicts_with_all = ["hotkey", "virtual_code", "key", "idle_lock", "bind"])]
+	once:                bool,
+	/// Print the virtual and scan codes of pressed keys, with a matching --bind, until Escape is pressed.
+	///
+	/// Helps finding the codes of keys that -k can't express, for -v or --bind.
+	#[arg(long, conflicts_with_all = ["once", "hotkey", "virtual_code", "key", "idle_lock", "bind"])]
+	capture:             bool,
+	/// List the key names and their virtual codes, then exit.
+	///
+	/// Includes the character keys of the current keyboard layout that have no name.
+	#[arg(long, conflicts_with_all = ["once", "hotkey", "virtual_code", "key", "idle_lock", "bind", "capture"])]
+	list_keys:           bool,
+	/// Print a completion script for the shell, e.g. powershell, bash, or zsh, then exit.
+	#[arg(long, value_name = "SHELL", hide = true)]
+	completions:         Option<clap_complete::Shell>,
+	/// Have Windows run winlock with the rest of the given arguments when the user logs in, then exit.
+	///
+	/// Flags that run once and exit (e.g. --once, --print-resolved) are left out.
+	/// Relative paths (e.g. --config's) are resolved from where Windows starts it, so make them absolute.
+	/// Since it starts without elevation, use --install-task instead for options that require it (e.g. --machine).
+	#[arg(long, conflicts_with_all = ["once", "capture", "list_keys"])]
+	install_autostart:   bool,
+	/// Stop Windows from running winlock when the user logs in, then exit.
+	#[arg(long, conflicts_with_all = ["once", "capture", "list_keys", "install_autostart"])]
+	uninstall_autostart: bool,
+	/// Have Task Scheduler run winlock elevated with the rest of the given arguments when the user logs in, then exit.
+	///
+	/// Like --install-autostart, but for options that require elevation (e.g. --machine), and it must run elevated too.
+	/// It's the alternative to running as a Windows service, which can't see the user's key presses nor lock the user's
+	/// session.
+	#[arg(long, conflicts_with_all = ["once", "capture", "list_keys", "install_autostart", "uninstall_autostart"])]
+	install_task:        bool,
+	/// Delete the task --install-task created, then exit.
+	#[arg(long, conflicts_with_all = ["once", "capture", "list_keys", "install_autostart", "uninstall_autostart", "install_task"])]
+	uninstall_task:      bool,
+	/// Run a command when the lock hotkey is pressed, instead of locking, e.g. --exec "nircmd monitor off".
+	///
+	/// The command is split into arguments by whitespace, except within double quotes, and isn't run by a shell.
+	/// It runs with winlock's privileges, which are elevated when winlock is, e.g. for --machine.
+	#[arg(long, value_name = "COMMAND", conflicts_with_all = ["once", "capture", "list_keys"])]
+	exec:                Option<String>,
+	/// Lock after starting the --exec command, without waiting for it to finish.
+	#[arg(long, requires = "exec")]
+	exec_lock:           bool,
+	/// Append a timestamped line to the file whenever winlock locks the workstation, and whenever the session is locked or
+	/// unlocked by any means.
+	///
+	/// Keeps running if the file can't be written, logging an error for each entry that fails.
+	#[arg(long, value_name = "PATH", conflicts_with_all = ["capture", "list_keys"])]
+	audit_log:           Option<PathBuf>,
+	/// Print the hotkeys the other flags resolve to, with their virtual codes and actions, then exit without registering them.
+	///
+	/// Shows e.g. the modifiers -k adds for characters that need them on the current keyboard layout.
+	#[arg(long, conflicts_with_all = ["once", "capture", "list_keys"])]
+	print_resolved:      bool,
+}
+
+#[derive(Debug, Hash, Default, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, clap::Args)]
 struct Options {
 	/// Disable the default Windows locking.
 	#[arg(short, long)]
-	disable_windows: bool,
-	/// Restore the default Windows locking at termination.
+	disable_windows:     bool,
+	/// Restore the Windows locking policy from before the program started at termination, including by Ctrl+C or a panic.
 	///
-	/// Tip: if you want to restore it at the start, invoke the program first with just the -r flag.
+	/// With nothing to run (no hotkey, --idle-lock, or --once), it enables the default Windows locking right away instead,
+	/// e.g. to restore it after an ungraceful exit.
 	#[arg(short, long)]
-	restore_windows: bool,
+	restore_windows:     bool,
+	/// Apply -d and -r to the locking policy of all users (HKEY_LOCAL_MACHINE) rather than the current user's.
+	///
+	/// Some machines, e.g. domain-joined ones, only honor this policy. Requires running elevated, and group policy may
+	/// overwrite it.
+	#[arg(long)]
+	machine:             bool,
+	/// Milliseconds to wait after locking before disabling the default Windows locking again (with -d).
+	///
+	/// Disabling too soon can prevent the lock altogether, so raise this if locking sometimes doesn't happen;
+	/// lower it to have the default locking disabled sooner. Defaults to 500.
+	#[arg(long)]
+	relock_delay_ms:     Option<u64>,
+	/// Also lock the workstation after this many seconds without user input.
+	///
+	/// It locks once per idle period: the countdown starts over only after the user is back.
+	#[arg(long, value_name = "SECONDS")]
+	idle_lock:           Option<u64>,
+	/// Also lock the workstation when the system is about to suspend, e.g. when the lid closes.
+	#[arg(long)]
+	lock_on_suspend:     bool,
+	/// Wait this many seconds after the lock hotkey is pressed before locking, during which Escape cancels it.
+	#[arg(long, value_name = "SECONDS")]
+	grace:               Option<u64>,
+	/// Ignore hotkey presses for this many milliseconds after one is handled, e.g. to not lock again while mashing it.
+	#[arg(long, value_name = "MILLISECONDS")]
+	cooldown_ms:         Option<u64>,
+	/// Ignore hotkey presses while the workstation is locked, e.g. so presses made on the lock screen don't lock it
+	/// again once unlocked.
+	///
+	/// Ignored presses don't start a --cooldown-ms.
+	#[arg(long)]
+	ignore_while_locked: bool,
+	/// Play the default system sound when a hotkey is pressed, before performing its action.
+	#[arg(long)]
+	beep:                bool,
+	/// The hotkey to press, e.g. "Ctrl+Alt+L".
+	///
+	/// An alternative to the -k/-v flags and the modifier flags, which are added to its modifiers.
+	#[arg(long)]
+	hotkey:              Option<Hotkey>,
 	/// Which key to press (virtual key code number).
 	///
 	/// Reference: https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
 	#[arg(short, long)]
-	virtual_code:    Option<u32>,
+	virtual_code:        Option<u32>,
 	#[arg(short, long)]
 	/// The key to press.
 	///
 	/// If it doesn't match to a character, see the -v flag.
-	key:             Option<char>,
+	/// Modifiers the character requires on the current layout (e.g. Shift for '?') are included automatically.
+	/// The key is resolved again when the keyboard layout of the focused window changes.
+	key:                 Option<char>,
 	/// Control modifier.
 	#[arg(short, long)]
-	ctrl:            bool,
+	ctrl:                bool,
 	/// Shift modifier.
 	#[arg(short, long)]
-	shift:           bool,
+	shift:               bool,
 	/// Windows modifier.
 	#[arg(short, long)]
-	windows:         bool,
+	windows:             bool,
 	/// Alt modifier.
 	#[arg(short, long)]
-	alt:             bool,
+	alt:                 bool,
+	/// Trigger the hotkeys repeatedly while they're held down, with the keyboard's auto-repeat.
+	///
+	/// By default a hotkey triggers once per press.
+	#[arg(long)]
+	repeat:              bool,
+	/// Register hotkeys even if they have no modifiers, which takes their key over system-wide.
+	#[arg(long)]
+	force:               bool,
+	/// Log the actions instead of performing them, including changes to the locking policy.
+	///
+	/// The hotkeys are still registered, e.g. to try out a configuration.
+	/// With --install-autostart or --install-task, it prints the command to run at login instead of installing it.
+	#[arg(long)]
+	dry_run:             bool,
 }
 
-#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, thiserror::Error)]
-pub enum OptionsKeyError {
-	#[error("failed to map the key to its virtual code")]
-	MappingFail,
-	#[error("two keyboard shortcuts were given")]
-	Conflict,
+/// Performs a non-locking action, honoring --dry-run, and --machine for toggle-lock.
+fn perform_action(action: &Action, options: Options) {
+	if options.dry_run {
+		tracing::info!("would {action}");
+		return;
+	}
+	let scope = options.policy_scope();
+	match action.perform_in(scope) {
+		Err(e) => tracing::error!("failed to {action}: {e}"),
+		Ok(()) if *action == Action::ToggleLockEnabled => {
+			let enabled = winlock::get_lock_enabled_in(scope).unwrap_or_default();
+			tracing::info!(
+				"locking is now {}",
+				if enabled { "enabled" } else { "disabled" }
+			);
+		}
+		Ok(()) => tracing::info!("performed {action}"),
+	}
 }
 
-impl Options {
-	fn virtual_key(self) -> Result<Option<Key>, OptionsKeyError> {
-		match (self.virtual_code, self.key) {
-			(None, None) => Ok(None),
-			(None, Some(button)) => Key::from_current_layout_char(button)
-				.map_or(Err(OptionsKeyError::MappingFail), |k| Ok(Some(k))),
-			(Some(code), None) => Ok(Some(Key(code))),
-			(Some(_), Some(_)) => Err(OptionsKeyError::Conflict),
-		}
+/// A hotkey bound to an action.
+#[derive(Debug, Hash, Clone, PartialEq, PartialOrd, Eq, Ord)]
+struct Binding {
+	hotkey: Hotkey,
+	action: Action,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseBindingError {
+	#[error("expected HOTKEY=ACTION")]
+	MissingAction,
+	#[error(transparent)]
+	Hotkey(#[from] winlock::ParseHotkeyError),
+	#[error(transparent)]
+	Action(#[from] winlock::ParseActionError),
+}
+
+impl FromStr for Binding {
+	type Err = ParseBindingError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		// the first `=` that ends a hotkey, since the hotkey's key may be `=` and an exec: command may contain some
+		let (hotkey, action) = s
+			.match_indices('=')
+			.map(|(i, _)| (&s[..i], &s[i + 1..]))
+			.find(|(hotkey, _)| hotkey.parse::<Hotkey>().is_ok())
+			.or_else(|| s.rsplit_once('='))
+			.ok_or(ParseBindingError::MissingAction)?;
+		Ok(Binding {
+			hotkey: hotkey.parse()?,
+			action: action.parse()?,
+		})
 	}
 }
 
-impl From<Options> for Modifiers {
-	fn from(value: Options) -> Self {
-		let mut result = Self::NoRepeat;
-		if value.ctrl {
-			result |= Modifiers::Control
+/// The options of a [`Cli::config`] file (see [`Options`]).
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct Config {
+	disable_windows:     Option<bool>,
+	restore_windows:     Option<bool>,
+	machine:             Option<bool>,
+	relock_delay_ms:     Option<u64>,
+	idle_lock:           Option<u64>,
+	lock_on_suspend:     Option<bool>,
+	grace:               Option<u64>,
+	cooldown_ms:         Option<u64>,
+	ignore_while_locked: Option<bool>,
+	beep:                Option<bool>,
+	hotkey:              Option<Hotkey>,
+	virtual_code:        Option<u32>,
+	key:                 Option<char>,
+	ctrl:                Option<bool>,
+	shift:               Option<bool>,
+	windows:             Option<bool>,
+	alt:                 Option<bool>,
+	repeat:              Option<bool>,
+	force:               Option<bool>,
+	dry_run:             Option<bool>,
+	bind:                Vec<String>,
+	profile:             BTreeMap<String, Config>,
+}
+
+impl Config {
+	/// The config with the options it doesn't set taken from the base, e.g. the command line's over a profile's over
+	/// the file's. Its lock hotkey's key and bindings replace the base's whole, if it has any.
+	fn or(self, base: Config) -> Config {
+		let has_key = self.hotkey.is_some() || self.virtual_code.is_some() || self.key.is_some();
+		let (hotkey, virtual_code, key) = if has_key {
+			(self.hotkey, self.virtual_code, self.key)
+		} else {
+			(base.hotkey, base.virtual_code, base.key)
+		};
+		Config {
+			disable_windows: self.disable_windows.or(base.disable_windows),
+			restore_windows: self.restore_windows.or(base.restore_windows),
+			machine: self.machine.or(base.machine),
+			relock_delay_ms: self.relock_delay_ms.or(base.relock_delay_ms),
+			idle_lock: self.idle_lock.or(base.idle_lock),
+			lock_on_suspend: self.lock_on_suspend.or(base.lock_on_suspend),
+			grace: self.grace.or(base.grace),
+			cooldown_ms: self.cooldown_ms.or(base.cooldown_ms),
+			ignore_while_locked: self.ignore_while_locked.or(base.ignore_while_locked),
+			beep: self.beep.or(base.beep),
+			hotkey,
+			virtual_code,
+			key,
+			ctrl: self.ctrl.or(base.ctrl),
+			shift: self.shift.or(base.shift),
+			windows: self.windows.or(base.windows),
+			alt: self.alt.or(base.alt),
+			repeat: self.repeat.or(base.repeat),
+			force: self.force.or(base.force),
+			dry_run: self.dry_run.or(base.dry_run),
+			bind: if self.bind.is_empty() {
+				base.bind
+			} else {
+				self.bind
+			},
+			profile: BTreeMap::new(),
 		}
-		if value.shift {
-			result |= Modifiers::Shift
+	}
+
+	/// The config's options, with the flags it doesn't set off.
+	fn options(&self) -> Options {
+		Options {
+			disable_windows:     self.disable_windows.unwrap_or(false),
+			restore_windows:     self.restore_windows.unwrap_or(false),
+			machine:             self.machine.unwrap_or(false),
+			relock_delay_ms:     self.relock_delay_ms,
+			idle_lock:           self.idle_lock,
+			lock_on_suspend:     self.lock_on_suspend.unwrap_or(false),
+			grace:               self.grace,
+			cooldown_ms:         self.cooldown_ms,
+			ignore_while_locked: self.ignore_while_locked.unwrap_or(false),
+			beep:                self.beep.unwrap_or(false),
+			hotkey:              self.hotkey,
+			virtual_code:        self.virtual_code,
+			key:                 self.key,
+			ctrl:                self.ctrl.unwrap_or(false),
+			shift:               self.shift.unwrap_or(false),
+			windows:             self.windows.unwrap_or(false),
+			alt:                 self.alt.unwrap_or(false),
+			repeat:              self.repeat.unwrap_or(false),
+			force:               self.force.unwrap_or(false),
+			dry_run:             self.dry_run.unwrap_or(false),
 		}
-		if value.windows {
-			result |= Modifiers::Win
+	}
+}
+
+impl From<Options> for Config {
+	/// The options as a config that sets the values given and the flags that are on.
+	fn from(options: Options) -> Self {
+		Config {
+			disable_windows: options.disable_windows.then_some(true),
+			restore_windows: options.restore_windows.then_some(true),
+			machine: options.machine.then_some(true),
+			relock_delay_ms: options.relock_delay_ms,
+			idle_lock: options.idle_lock,
+			lock_on_suspend: options.lock_on_suspend.then_some(true),
+			grace: options.grace,
+			cooldown_ms: options.cooldown_ms,
+			ignore_while_locked: options.ignore_while_locked.then_some(true),
+			beep: options.beep.then_some(true),
+			hotkey: options.hotkey,
+			virtual_code: options.virtual_code,
+			key: options.key,
+			ctrl: options.ctrl.then_some(true),
+			shift: options.shift.then_some(true),
+			windows: options.windows.then_some(true),
+			alt: options.alt.then_some(true),
+			repeat: options.repeat.then_some(true),
+			force: options.force.then_some(true),
+			dry_run: options.dry_run.then_some(true),
+			..Default::default()
 		}
-		if value.alt {
-			result |= Modifiers::Alt
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+	#[error("failed to read the config file: {0}")]
+	Read(#[from] io::Error),
+	#[error("failed to parse the config file: {0}")]
+	Parse(#[from] toml::de::Error),
+	#[error("invalid binding in the config file: {0}")]
+	Binding(#[from] ParseBindingError),
+	#[error("the config file has no profile \"{0}\"")]
+	UnknownProfile(String),
+}
+
+impl Cli {
+	/// The options and bindings, merged with the config file's if there is one.
+	fn resolve(self) -> Result<(Options, Vec<Binding>), ConfigError> {
+		let Some(path) = self.config else {
+			return Ok((self.options, self.bind));
+		};
+		let mut config: Config = toml::from_str(&fs::read_to_string(path)?)?;
+		if let Some(name) = &self.profile {
+			let profile = config
+				.profile
+				.remove(name)
+				.ok_or_else(|| ConfigError::UnknownProfile(name.clone()))?;
+			config = profile.or(config);
 		}
-		result
+		let config = Config::from(self.options).or(config);
+		let bindings = if self.bind.is_empty() {
+			config
+				.bind
+				.iter()
+				.map(|binding| binding.parse())
+				.collect::<Result<_, _>>()?
+		} else {
+			self.bind
+		};
+		Ok((config.options(), bindings))
 	}
 }
 
-fn disable_lock() {
+impl Options {
+	/// The lock hotkey's flags.
+	fn hotkey_options(self) -> HotkeyOptions {
+		HotkeyOptions {
+			hotkey:       self.hotkey,
+			virtual_code: self.virtual_code,
+			key:          self.key,
+			ctrl:         self.ctrl,
+			shift:        self.shift,
+			win:          self.windows,
+			alt:          self.alt,
+			repeat:       self.repeat,
+		}
+	}
+
+	/// The hotkey to lock with on the keyboard layout.
+	fn lock_hotkey(self, layout: KeyboardLayout) -> Result<Option<Hotkey>, OptionsKeyError> {
+		self.hotkey_options().into_hotkey_on(layout)
+	}
+}
+
+fn disable_lock(options: Options) {
 	tracing::info_span!("disabling windows lock").in_scope(|| {
-		if let Err(e) = winlock::set_lock_enabled(false) {
+		if options.dry_run {
+			tracing::info!("would disable");
+		} else if let Err(e) = winlock::set_lock_enabled_in(options.policy_scope(), false) {
 			tracing::error!("failed to disable locking: {e}");
 		} else {
 			tracing::info!("disabled")
@@ -88,9 +448,11 @@ fn disable_lock() {
 	})
 }
 
-fn enable_lock() {
+fn enable_lock(options: Options) {
 	tracing::info_span!("enabling windows lock").in_scope(|| {
-		if let Err(e) = winlock::set_lock_enabled(true) {
+		if options.dry_run {
+			tracing::info!("would enable");
+		} else if let Err(e) = winlock::set_lock_enabled_in(options.policy_scope(), true) {
 			tracing::error!("failed to restore locking: {e}");
 		} else {
 			tracing::info!("enabled")
@@ -98,102 +460,709 @@ fn enable_lock() {
 	})
 }
 
+/// Restores the locking policy, or enables locking if the policy couldn't be read at the start.
+fn restore_lock(options: Options, original: Option<LockPolicySnapshot>) {
+	let Some(original) = original else {
+		return enable_lock(options);
+	};
+	tracing::info_span!("restoring windows lock").in_scope(|| {
+		if options.dry_run {
+			tracing::info!(enabled = original.lock_enabled(), "would restore");
+		} else if let Err(e) = original.restore() {
+			tracing::error!("failed to restore locking: {e}");
+		} else {
+			tracing::info!(enabled = original.lock_enabled(), "restored")
+		}
+	})
+}
+
 impl Options {
-	fn cleanup(self) {
+	/// Where the locking policy is.
+	fn policy_scope(self) -> PolicyScope {
+		if self.machine {
+			PolicyScope::Machine
+		} else {
+			PolicyScope::User
+		}
+	}
+
+	/// The locking policy to restore at termination (with -r).
+	fn original_policy(self) -> Option<LockPolicySnapshot> {
+		if !self.restore_windows {
+			return None;
+		}
+		LockPolicySnapshot::take_in(self.policy_scope())
+			.map_err(|e| {
+				tracing::warn!(
+					"failed to read the locking policy, so it will be enabled instead: {e}"
+				)
+			})
+			.ok()
+	}
+
+	fn cleanup(self, original: Option<LockPolicySnapshot>) {
 		tracing::info_span!("shutting down").in_scope(|| {
 			if self.restore_windows {
-				enable_lock();
+				restore_lock(self, original);
 			}
 		})
 	}
 }
 
-fn main() {
-	let options = Options::parse();
+/// Prints the pressed keys until Escape is pressed.
+fn capture() -> io::Result<()> {
+	eprintln!("Press keys to see their codes, or Escape to quit.");
+	loop {
+		let CapturedKey { hotkey, scan_code } = winlock::capture_hotkey()?;
+		if hotkey.key_code == keys::ESCAPE && hotkey.modifiers.is_empty() {
+			return Ok(());
+		}
+		println!(
+			"{} (virtual code 0x{:02X}, scan code 0x{scan_code:02X}): --bind \"{hotkey}=lock\"",
+			hotkey.key_code, hotkey.key_code.0
+		);
+	}
+}
 
-	{
-		let subscriber = tracing_subscriber::fmt()
-			.with_env_filter(
-				EnvFilter::builder()
-					.with_env_var("WINLOCK_LOG")
-					.with_default_directive(LevelFilter::INFO.into())
-					.from_env_lossy(),
-			)
-			.finish();
-		let _ = tracing::subscriber::set_global_default(subscriber)
-			.map_err(|e| eprintln!("failed to set up logging: {e}"));
+fn list_keys() {
+	let mut rows = Vec::<(String, u32, Vec<&str>)>::new();
+	for (name, key) in keys::names() {
+		match rows.last_mut() {
+			Some((_, code, aliases)) if *code == key.0 => aliases.push(name),
+			_ => rows.push((name.to_owned(), key.0, Vec::new())),
+		}
+	}
+	rows.extend(
+		(1..=0xFE)
+			.map(Key::new)
+			.filter(|key| key.name().is_none())
+			.filter_map(|key| {
+				let c = key
+					.to_char()
+					.filter(|c| !c.is_control() && !c.is_whitespace())?;
+				Some((c.to_string(), key.0, Vec::new()))
+			}),
+	);
+	rows.sort_by_key(|&(_, code, _)| code);
+	let width = rows
+		.iter()
+		.map(|(name, ..)| name.chars().count())
+		.max()
+		.unwrap_or(0);
+	for (name, code, aliases) in rows {
+		let line = format!("{name:width$}  0x{code:02X}  {}", aliases.join(", "));
+		println!("{}", line.trim_end());
+	}
+}
+
+/// The name winlock's login command is registered under.
+const AUTOSTART_NAME: &str = "winlock";
+
+/// Quotes a command line argument if it needs to be, escaping it the way the C runtime parses it.
+fn quote(arg: &str) -> String {
+	if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+		return arg.to_owned();
+	}
+	let mut quoted = String::from('"');
+	let mut backslashes = 0;
+	for c in arg.chars() {
+		match c {
+			'\\' => backslashes += 1,
+			'"' => {
+				quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+				quoted.push('"');
+				backslashes = 0;
+			}
+			c => {
+				quoted.extend(std::iter::repeat_n('\\', backslashes));
+				quoted.push(c);
+				backslashes = 0;
+			}
+		}
+	}
+	// backslashes before the closing quote would escape it
+	quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+	quoted.push('"');
+	quoted
+}
+
+/// The flags of runs that do something and exit instead of listening, which running at login leaves out.
+const ONE_SHOT_FLAGS: [&str; 9] = [
+	"once",
+	"capture",
+	"list_keys",
+	"completions",
+	"install_autostart",
+	"uninstall_autostart",
+	"install_task",
+	"uninstall_task",
+	"print_resolved",
+];
+
+/// The command line of the program with the arguments it was given, spelled out as long flags, except for the
+/// [`ONE_SHOT_FLAGS`], and for --dry-run when previewing the command with it.
+fn autostart_command(preview: bool) -> io::Result<String> {
+	let exe = std::env::current_exe()?;
+	let matches = Cli::command().get_matches();
+	let mut command = vec![quote(&exe.to_string_lossy())];
+	for arg in Cli::command().get_arguments() {
+		let id = arg.get_id().as_str();
+		if ONE_SHOT_FLAGS.contains(&id)
+			|| (preview && id == "dry_run")
+			|| matches.value_source(id) != Some(ValueSource::CommandLine)
+		{
+			continue;
+		}
+		let flag = match (arg.get_long(), arg.get_short()) {
+			(Some(long), _) => format!("--{long}"),
+			(None, Some(short)) => format!("-{short}"),
+			(None, None) => continue,
+		};
+		if arg.get_action().takes_values() {
+			for value in matches.get_raw(id).into_iter().flatten() {
+				command.push(quote(&format!("{flag}={}", value.to_string_lossy())));
+			}
+		} else {
+			command.push(flag);
+		}
+	}
+	Ok(command.join(" "))
+}
+
+/// Registers the program to run at login with the arguments it was given (see [`autostart_command`]).
+fn install_autostart(dry_run: bool) -> io::Result<()> {
+	let command = autostart_command(dry_run)?;
+	if dry_run {
+		println!("{command}");
+		return Ok(());
+	}
+	winlock::enable_autostart(AUTOSTART_NAME, &command)?;
+	tracing::info!("registered to run at login: {command}");
+	Ok(())
+}
+
+/// Schedules the program to run elevated at login with the arguments it was given (see [`autostart_command`]).
+fn install_task(dry_run: bool) -> io::Result<()> {
+	let command = autostart_command(dry_run)?;
+	if dry_run {
+		println!("{command}");
+		return Ok(());
+	}
+	winlock::enable_task_autostart(AUTOSTART_NAME, &command)?;
+	tracing::info!("scheduled to run elevated at login: {command}");
+	Ok(())
+}
+
+/// A command to run instead of locking when the lock hotkey is pressed (--exec).
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+struct Exec {
+	/// The [`Action::Exec`] of the command.
+	command: Action,
+	/// Whether to lock after starting it.
+	lock:    bool,
+}
+
+impl Exec {
+	/// [`None`] if the command line has no program.
+	fn parse(command: &str, lock: bool) -> Option<Self> {
+		Action::exec(command).map(|command| Self { command, lock })
+	}
+}
+
+/// Performs the action, running the --exec command instead of locking if there is one.
+fn perform(
+	action: &Action,
+	options: Options,
+	exec: Option<&Exec>,
+	audit: Option<&mut AuditLogger>,
+) {
+	match (action, exec) {
+		(Action::Lock, Some(exec)) => {
+			perform_action(&exec.command, options);
+			if exec.lock {
+				audit_lock(options, audit, "hotkey");
+			}
+		}
+		(Action::Lock, None) => audit_lock(options, audit, "hotkey"),
+		_ => perform_action(action, options),
 	}
+}
+
+/// Locks the workstation, and records it in the --audit-log, mentioning what triggered it.
+fn audit_lock(options: Options, audit: Option<&mut AuditLogger>, cause: &str) {
+	if lock(options).is_ok() && !options.dry_run {
+		audit_log(audit, &format!("locked by {cause}"));
+	}
+}
+
+/// Records the event in the --audit-log, if there is one.
+fn audit_log(audit: Option<&mut AuditLogger>, event: &str) {
+	if let Some(audit) = audit {
+		if let Err(e) = audit.log(event) {
+			tracing::error!("failed to write to the audit log: {e}");
+		}
+	}
+}
+
+/// How often to check the idle time when locking on idle, and the keyboard layout when the hotkey's key is a character.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
+fn lock(options: Options) -> io::Result<()> {
+	enable_lock(options);
+	let result = tracing::info_span!("locking workspace").in_scope(|| {
+		if options.dry_run {
+			tracing::info!("would lock the workstation");
+			return Ok(());
+		}
+		match winlock::lock_workstation_checked() {
+			Ok(LockOutcome::Issued) => tracing::info!("locking the workstation"),
+			Ok(LockOutcome::Suppressed) => tracing::warn!(
+				"locking the workstation, though a policy likely disables locking (see --machine)"
+			),
+			Err(LockError::AlreadyLocked) => tracing::info!("the workstation is already locked"),
+			Err(e) => {
+				tracing::error!("failed to lock the workstation: {e}");
+				return Err(e.into());
+			}
+		}
+		Ok(())
+	});
 	if options.disable_windows {
-		disable_lock();
+		// sleep for a bit to avoid race condition (see `set_lock_enabled`'s documentation).
+		tracing::debug!("waiting for lock screen before disabling lock");
+		std::thread::sleep(
+			options
+				.relock_delay_ms
+				.map_or(winlock::RELOCK_DELAY, Duration::from_millis),
+		);
+		disable_lock(options);
+	}
+	result
+}
+
+/// Registers the lock hotkey anew for the keyboard layout, if its key resolves differently on it.
+fn rebind_lock(
+	options: Options,
+	layout: KeyboardLayout,
+	hotkeys: &mut HotkeyManager,
+	(id, _): &mut (HotkeyId, Action),
+) {
+	let hotkey = match options.lock_hotkey(layout) {
+		Ok(Some(hotkey)) => hotkey,
+		Ok(None) => return,
+		Err(e) => {
+			tracing::warn!("keeping the hotkey after a keyboard layout change: {e}");
+			return;
+		}
+	};
+	if hotkeys.get(*id) == Some(hotkey) {
+		return;
+	}
+	if let Err(e) = hotkeys.unregister(*id) {
+		tracing::error!("failed to unregister the hotkey: {e}");
 	}
+	match hotkeys.register(hotkey) {
+		Ok(new_id) => {
+			*id = new_id;
+			tracing::info!("registered {hotkey} for the new keyboard layout");
+		}
+		Err(e) => tracing::error!("failed to register {hotkey} for the new keyboard layout: {e}"),
+	}
+}
 
-	match options.virtual_key() {
-		Ok(Some(key_code)) => {
-			let register_result = winlock::Hotkey {
-				modifiers: Modifiers::from(options),
-				key_code,
+/// How a [`grace`] period ended.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+enum Grace {
+	Lock,
+	Cancel,
+	Quit,
+}
+
+/// Waits out the grace period before locking, during which Escape is registered as a hotkey that cancels it.
+fn grace(hotkeys: &mut HotkeyManager, period: Duration) -> Grace {
+	tracing::info!("locking in {}s, press Escape to cancel", period.as_secs());
+	let cancel = match hotkeys.register(Hotkey::new(Modifiers::NoRepeat, keys::ESCAPE)) {
+		Ok(id) => Some(id),
+		Err(e) => {
+			tracing::warn!("failed to register Escape, so locking can't be cancelled: {e}");
+			None
+		}
+	};
+	let deadline = Instant::now() + period;
+	let grace = loop {
+		let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+			break Grace::Lock;
+		};
+		match winlock::await_event_timeout(remaining) {
+			Ok(Some(HotkeyEvent::Hotkey { id })) if Some(id) == cancel => break Grace::Cancel,
+			Ok(Some(HotkeyEvent::Quit)) => break Grace::Quit,
+			Ok(_) => {}
+			Err(e) => {
+				tracing::error!("failed to listen to a message from Windows: {e}");
+				break Grace::Lock;
 			}
-			.register();
-			if let Err(e) = register_result {
-				tracing::error!("failed to register the hotkey in the system: {e}, terminating.");
-				options.cleanup();
-				std::process::exit(1);
+		}
+	};
+	if let Some(id) = cancel {
+		if let Err(e) = hotkeys.unregister(id) {
+			tracing::error!("failed to unregister Escape: {e}");
+		}
+	}
+	grace
+}
+
+/// Runs the message loop, performing the actions bound to the hotkey identifiers.
+///
+/// When the lock hotkey's key is a character (-k), its binding comes first, and it follows the keyboard layout.
+fn run(
+	options: Options,
+	exec: Option<&Exec>,
+	mut audit: Option<&mut AuditLogger>,
+	hotkeys: &mut HotkeyManager,
+	actions: &mut [(HotkeyId, Action)],
+) {
+	let idle_lock = options.idle_lock.map(Duration::from_secs);
+	let mut idle_locked = false;
+	let follow_layout = options.key.is_some() && !actions.is_empty();
+	let mut layout = KeyboardLayout::foreground();
+	let mut cooldown = options
+		.cooldown_ms
+		.map(|cooldown| Debounce::new(Duration::from_millis(cooldown)));
+	loop {
+		let event = if idle_lock.is_some() || follow_layout {
+			winlock::await_event_timeout(CHECK_INTERVAL)
+		} else {
+			winlock::await_event().map(Some)
+		};
+		// hotkeys registered on the thread by other than winlock aren't its to act on
+		let ids: Vec<HotkeyId> = actions.iter().map(|&(id, _)| id).collect();
+		let event = event.map(|event| event.map(|event| event.only_among(&ids)));
+		if follow_layout {
+			let foreground = KeyboardLayout::foreground();
+			if foreground != layout {
+				layout = foreground;
+				tracing::debug!("the keyboard layout changed");
+				rebind_lock(options, layout, hotkeys, &mut actions[0]);
 			}
-			if options.restore_windows {
-				let _ = ctrlc::set_handler(move || {
-					options.cleanup();
-					std::process::exit(0);
-				})
-				.map_err(|e| tracing::warn!("failed to hook restoration on termination: {e}"));
-			}
-			loop {
-				let event = match winlock::await_event() {
-					Ok(event) => event,
-					Err(error) => {
-						tracing::error!("failed to listen to a message from Windows: {error}");
-						continue;
-					}
-				};
-				match event {
-					HotkeyEvent::Hotkey => {
-						tracing::info!("detected hotkey press");
-					}
-					HotkeyEvent::Other => {
-						tracing::debug!(
-							"received an irrelevant Windows message (not a hotkey or quit)"
-						);
-						continue;
-					}
-					HotkeyEvent::Quit => {
-						tracing::debug!("got WM_QUIT");
-						break;
-					}
-				}
-				enable_lock();
-				tracing::info_span!("locking workspace").in_scope(|| {
-					if let Err(e) = winlock::lock_workstation() {
-						tracing::error!("failed to lock the workstation: {e}");
-					} else {
-						tracing::info!("locking the workstation");
+		}
+		let action = match event {
+			Ok(Some(HotkeyEvent::Hotkey { .. }))
+				if options.ignore_while_locked
+					&& winlock::is_workstation_locked().unwrap_or(false) =>
+			{
+				tracing::debug!("ignoring a hotkey press while the workstation is locked");
+				None
+			}
+			Ok(Some(HotkeyEvent::Hotkey { .. }))
+				if cooldown.as_mut().is_some_and(|cooldown| !cooldown.ready()) =>
+			{
+				tracing::debug!("ignoring a hotkey press during the cooldown");
+				None
+			}
+			Ok(Some(HotkeyEvent::Hotkey { id })) => {
+				tracing::info!("detected hotkey press");
+				if options.beep {
+					if let Err(e) = winlock::beep() {
+						tracing::warn!("failed to beep: {e}");
 					}
-				});
-				if options.disable_windows {
-					// sleep for a bit to avoid race condition (see `set_lock_enabled`'s documentation).
-					tracing::debug!("waiting for lock screen before disabling lock");
-					std::thread::sleep(Duration::from_millis(500));
-					disable_lock();
 				}
+				actions
+					.iter()
+					.find_map(|(action_id, action)| (*action_id == id).then_some(action))
+			}
+			Ok(Some(HotkeyEvent::Power(PowerEvent::Suspend))) if options.lock_on_suspend => {
+				tracing::info!("detected suspension");
+				audit_lock(options, audit.as_deref_mut(), "suspension");
+				None
 			}
+			Ok(Some(HotkeyEvent::Session(event))) => {
+				tracing::debug!("the session changed: {event:?}");
+				let event = match event {
+					SessionEvent::Lock => "session locked",
+					SessionEvent::Unlock => "session unlocked",
+				};
+				audit_log(audit.as_deref_mut(), event);
+				None
+			}
+			Ok(Some(
+				HotkeyEvent::Power(_)
+				| HotkeyEvent::Released { .. }
+				| HotkeyEvent::User(_)
+				| HotkeyEvent::Other,
+			)) => {
+				tracing::debug!("received an irrelevant Windows message (not a hotkey or quit)");
+				None
+			}
+			Ok(Some(HotkeyEvent::Quit)) => {
+				tracing::debug!("got WM_QUIT");
+				break;
+			}
+			Ok(None) => None,
+			Err(error) => {
+				tracing::error!("failed to listen to a message from Windows: {error}");
+				None
+			}
+		};
+		let idle = idle_lock.is_some_and(|idle_lock| match winlock::idle_duration() {
+			Ok(idle) if idle < idle_lock => {
+				idle_locked = false;
+				false
+			}
+			Ok(_) => !std::mem::replace(&mut idle_locked, true),
+			Err(e) => {
+				tracing::error!("failed to get the idle time: {e}");
+				false
+			}
+		});
+		if idle {
+			tracing::info!("detected idling");
+			audit_lock(options, audit.as_deref_mut(), "idling");
+		} else if let Some(action) = action {
+			match (action, options.grace) {
+				(Action::Lock, Some(period)) => match grace(hotkeys, Duration::from_secs(period)) {
+					Grace::Lock => perform(action, options, exec, audit.as_deref_mut()),
+					Grace::Cancel => tracing::info!("cancelled locking"),
+					Grace::Quit => break,
+				},
+				_ => perform(action, options, exec, audit.as_deref_mut()),
+			}
+			let drained = winlock::drain_events();
+			if drained > 0 {
+				tracing::debug!(
+					"ignored {drained} hotkey presses made while handling the last one"
+				);
+			}
+		}
+	}
+}
+
+fn main() {
+	let cli = Cli::parse();
+
+	if let Some(shell) = cli.completions {
+		let mut command = Cli::command();
+		let name = command.get_name().to_owned();
+		clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+		return;
+	}
+
+	{
+		let filter = if cli.quiet {
+			EnvFilter::default().add_directive(LevelFilter::ERROR.into())
+		} else {
+			EnvFilter::builder()
+				.with_env_var("WINLOCK_LOG")
+				.with_default_directive(LevelFilter::INFO.into())
+				.from_env_lossy()
+		};
+		// stdout is left to results, e.g. of --print-resolved
+		let subscriber = tracing_subscriber::fmt()
+			.with_writer(io::stderr)
+			.with_env_filter(filter)
+			.finish();
+		let _ = tracing::subscriber::set_global_default(subscriber)
+			.map_err(|e| eprintln!("failed to set up logging: {e}"));
+	}
+
+	if cli.install_autostart || cli.uninstall_autostart {
+		let result = if cli.install_autostart {
+			install_autostart(cli.options.dry_run)
+		} else {
+			winlock::disable_autostart(AUTOSTART_NAME)
+				.map(|()| tracing::info!("unregistered from running at login"))
+		};
+		if let Err(e) = result {
+			tracing::error!("failed to change running at login: {e}");
+			std::process::exit(1);
 		}
-		Ok(None) => {}
+		return;
+	}
+
+	if cli.install_task || cli.uninstall_task {
+		let result = if cli.install_task {
+			install_task(cli.options.dry_run)
+		} else {
+			winlock::disable_task_autostart(AUTOSTART_NAME)
+				.map(|()| tracing::info!("unscheduled from running at login"))
+		};
+		if let Err(e) = result {
+			tracing::error!("failed to change the scheduled task: {e}");
+			std::process::exit(1);
+		}
+		return;
+	}
+
+	if cli.list_keys {
+		list_keys();
+		return;
+	}
+
+	if cli.capture {
+		if let Err(e) = capture() {
+			tracing::error!("failed to capture keys: {e}");
+			std::process::exit(1);
+		}
+		return;
+	}
+
+	let once = cli.once;
+	let print_resolved = cli.print_resolved;
+	let exec = match cli
+		.exec
+		.as_deref()
+		.map(|command| Exec::parse(command, cli.exec_lock))
+	{
+		Some(None) => {
+			tracing::error!("the --exec command is empty");
+			std::process::exit(1);
+		}
+		exec => exec.flatten(),
+	};
+	let mut audit = cli.audit_log.as_ref().and_then(|path| {
+		AuditLogger::open(path)
+			.map_err(|e| tracing::error!("failed to open the audit log {}: {e}", path.display()))
+			.ok()
+	});
+	let (options, bindings) = match cli.resolve() {
+		Ok(resolved) => resolved,
+		Err(e) => {
+			tracing::error!("{e}");
+			std::process::exit(1);
+		}
+	};
+
+	let lock_hotkey = match options.lock_hotkey(KeyboardLayout::foreground()) {
+		Ok(lock_hotkey) => lock_hotkey,
 		Err(e) => {
 			tracing::error!("{e}");
 			std::process::exit(1);
 		}
+	};
+	let lock_binding = lock_hotkey.map(|hotkey| Binding {
+		hotkey,
+		action: Action::Lock,
+	});
+	let no_repeat = if options.repeat {
+		Modifiers::empty()
+	} else {
+		Modifiers::NoRepeat
+	};
+	let bindings = lock_binding
+		.into_iter()
+		.chain(bindings.into_iter().map(|binding| Binding {
+			hotkey: Hotkey {
+				modifiers: binding.hotkey.modifiers | no_repeat,
+				..binding.hotkey
+			},
+			..binding
+		}))
+		.collect::<Vec<_>>();
+	if print_resolved {
+		for binding in &bindings {
+			let hotkey = binding.hotkey;
+			println!(
+				"{hotkey} (VK 0x{:02X}) = {}",
+				hotkey.key_code.0, binding.action
+			);
+		}
+		return;
 	}
 
+	let original = options.original_policy();
 	if options.restore_windows {
-		enable_lock();
+		// restores before the panic message, since the process might be aborted or killed after it
+		let panic_hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(move |info| {
+			options.cleanup(original);
+			panic_hook(info);
+		}));
+	}
+	if options.disable_windows {
+		disable_lock(options);
+	}
+
+	if once {
+		let result = lock(options);
+		if result.is_ok() && !options.dry_run {
+			audit_log(audit.as_mut(), "locked once");
+		}
+		options.cleanup(original);
+		std::process::exit(if result.is_ok() { 0 } else { 1 });
+	}
+
+	let mut hotkeys = HotkeyManager::new();
+	let mut actions = Vec::new();
+	for binding in bindings {
+		if let Err(e) = binding.hotkey.validate() {
+			if options.force {
+				tracing::warn!("registering the hotkey {} anyway: {e}", binding.hotkey);
+			} else {
+				tracing::error!(
+					"refusing to register the hotkey {}: {e}; pass --force to register it anyway, terminating.",
+					binding.hotkey
+				);
+				drop(hotkeys);
+				options.cleanup(original);
+				std::process::exit(1);
+			}
+		}
+		match hotkeys.register(binding.hotkey) {
+			Ok(id) => actions.push((id, binding.action)),
+			Err(e) => {
+				let hotkey = binding.hotkey;
+				match e {
+					RegisterError::AlreadyRegistered
+						if hotkeys.hotkeys().iter().any(|(_, other)| other.matches(&hotkey)) =>
+					{
+						tracing::error!("the hotkey {hotkey} is bound more than once, terminating.")
+					}
+					RegisterError::AlreadyRegistered => tracing::error!(
+						"the hotkey {hotkey} is already in use by another application (or another instance of winlock); \
+						 try a different combination. Terminating."
+					),
+					e => tracing::error!(
+						"failed to register the hotkey {hotkey} in the system: {e}, terminating."
+					),
+				}
+				drop(hotkeys);
+				options.cleanup(original);
+				std::process::exit(1);
+			}
+		}
+	}
+	let _power_notifications = options
+		.lock_on_suspend
+		.then(|| {
+			PowerNotifications::register()
+				.map_err(|e| tracing::error!("failed to subscribe to suspension: {e}"))
+				.ok()
+		})
+		.flatten();
+	let _session_notifications = audit
+		.is_some()
+		.then(|| {
+			SessionNotifications::register()
+				.map_err(|e| tracing::error!("failed to subscribe to session changes: {e}"))
+				.ok()
+		})
+		.flatten();
+	if !actions.is_empty() || options.idle_lock.is_some() || options.lock_on_suspend {
+		if options.restore_windows {
+			let _ = ctrlc::set_handler(move || {
+				options.cleanup(original);
+				std::process::exit(0);
+			})
+			.map_err(|e| tracing::warn!("failed to hook restoration on termination: {e}"));
+		}
+		run(
+			options,
+			exec.as_ref(),
+			audit.as_mut(),
+			&mut hotkeys,
+			&mut actions,
+		);
+		options.cleanup(original);
+	} else if options.restore_windows {
+		enable_lock(options);
 	}
 }
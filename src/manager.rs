@@ -0,0 +1,116 @@
+use std::io;
+
+use crate::{
+	Hotkey, HotkeyEvent, HotkeyId, PowerEvent, RegisterError, SessionEvent, SystemApi, WinApi,
+};
+
+/// Keeps track of registered [`Hotkey`]s, handing out their identifiers and unregistering them when dropped.
+///
+/// Hotkeys are registered to the calling thread, so the manager should be used and dropped on the thread that pumps the messages.
+///
+/// It goes through the system's [`WinApi`] unless created [`with_api`](HotkeyManager::with_api).
+#[derive(Debug, Default)]
+pub struct HotkeyManager<A: WinApi = SystemApi> {
+	api:     A,
+	hotkeys: Vec<(HotkeyId, Hotkey)>,
+}
+
+/// An event from the Windows message loop in the context of a [`HotkeyManager`].
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum ManagerEvent {
+	/// A hotkey of the manager was pressed.
+	Hotkey {
+		/// The identifier the manager registered the hotkey under.
+		id:     HotkeyId,
+		/// The pressed hotkey.
+		hotkey: Hotkey,
+	},
+	/// A user message was posted (see [`HotkeyEvent::User`]).
+	User(usize),
+	/// The session's state changed (see [`HotkeyEvent::Session`]).
+	Session(SessionEvent),
+	/// The system's power state changed (see [`HotkeyEvent::Power`]).
+	Power(PowerEvent),
+	/// An irrelevant event occurred, including presses of hotkeys the manager doesn't own.
+	Other,
+	/// Got a quit signal.
+	Quit,
+}
+
+impl HotkeyManager {
+	/// Creates a manager with no hotkeys.
+	pub fn new() -> Self { Self::default() }
+}
+
+impl<A: WinApi> HotkeyManager<A> {
+	/// Creates a manager with no hotkeys that goes through the given [`WinApi`].
+	pub fn with_api(api: A) -> Self {
+		Self {
+			api,
+			hotkeys: Vec::new(),
+		}
+	}
+
+	/// The [`WinApi`] the manager goes through.
+	pub fn api(&self) -> &A { &self.api }
+
+	/// Registers the [`Hotkey`] under a fresh identifier, which is returned.
+	pub fn register(&mut self, hotkey: Hotkey) -> Result<HotkeyId, RegisterError> {
+		let id = HotkeyId::next();
+		hotkey.register_with_api(id, &self.api)?;
+		self.hotkeys.push((id, hotkey));
+		Ok(id)
+	}
+
+	/// Unregisters the hotkey registered under the given identifier.
+	pub fn unregister(&mut self, id: HotkeyId) -> io::Result<()> {
+		let index = self
+			.hotkeys
+			.iter()
+			.position(|&(hotkey_id, _)| hotkey_id == id)
+			.ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::NotFound,
+					"no hotkey is registered under the id",
+				)
+			})?;
+		self.hotkeys[index].1.unregister_with_api(id, &self.api)?;
+		self.hotkeys.remove(index);
+		Ok(())
+	}
+
+	/// Gets the hotkey registered under the given identifier.
+	pub fn get(&self, id: HotkeyId) -> Option<Hotkey> {
+		self.hotkeys
+			.iter()
+			.find_map(|&(hotkey_id, hotkey)| (hotkey_id == id).then_some(hotkey))
+	}
+
+	/// The registered hotkeys and their identifiers.
+	pub fn hotkeys(&self) -> &[(HotkeyId, Hotkey)] { &self.hotkeys }
+
+	/// Blocks until the next Windows message.
+	///
+	/// See [`await_event`](crate::await_event).
+	pub fn await_event(&self) -> io::Result<ManagerEvent> {
+		Ok(match crate::await_event_with(&self.api)? {
+			HotkeyEvent::Hotkey { id } => match self.get(id) {
+				Some(hotkey) => ManagerEvent::Hotkey { id, hotkey },
+				None => ManagerEvent::Other,
+			},
+			HotkeyEvent::User(payload) => ManagerEvent::User(payload),
+			HotkeyEvent::Session(event) => ManagerEvent::Session(event),
+			HotkeyEvent::Power(event) => ManagerEvent::Power(event),
+			HotkeyEvent::Released { .. } | HotkeyEvent::Other => ManagerEvent::Other,
+			HotkeyEvent::Quit => ManagerEvent::Quit,
+		})
+	}
+}
+
+impl<A: WinApi> Drop for HotkeyManager<A> {
+	fn drop(&mut self) {
+		for &(id, hotkey) in &self.hotkeys {
+			let _ = hotkey.unregister_with_api(id, &self.api);
+		}
+	}
+}
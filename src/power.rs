@@ -0,0 +1,68 @@
+use std::io;
+
+use windows::Win32::{
+	Foundation::HANDLE,
+	System::Power::{
+		RegisterSuspendResumeNotification, UnregisterSuspendResumeNotification, HPOWERNOTIFY,
+	},
+	UI::WindowsAndMessaging::DEVICE_NOTIFY_WINDOW_HANDLE,
+};
+
+use crate::window::MessageWindow;
+
+/// A change in the system's power state.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum PowerEvent {
+	/// The system is about to suspend, e.g. to sleep because the lid closed.
+	///
+	/// The system suspends about two seconds after notifying, so it should be handled promptly, e.g. by
+	/// [`lock_workstation`](crate::lock_workstation) to have the session locked when the system resumes.
+	Suspend,
+	/// The system resumed from suspension.
+	Resume,
+}
+
+/// A subscription to the system's suspend and resume notifications, received by the message loop as
+/// [`HotkeyEvent::Power`](crate::HotkeyEvent::Power) events, until dropped.
+///
+/// Like hotkeys, the notifications are delivered to the thread that subscribes, which should be the one that pumps the messages.
+///
+/// Corresponds to [RegisterSuspendResumeNotification](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registersuspendresumenotification).
+#[derive(Debug)]
+pub struct PowerNotifications {
+	handle:  HPOWERNOTIFY,
+	/// The window the notifications are sent to.
+	_window: MessageWindow,
+}
+
+impl PowerNotifications {
+	/// Subscribes the calling thread to the system's notifications.
+	///
+	/// Requires Windows 8 or later.
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn register() -> io::Result<Self> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let window = MessageWindow::new()?;
+		// message-only windows don't receive the power broadcasts unless registered for them
+		let handle = unsafe {
+			RegisterSuspendResumeNotification(
+				HANDLE(window.handle().0),
+				DEVICE_NOTIFY_WINDOW_HANDLE,
+			)
+		}?;
+		Ok(Self {
+			handle,
+			_window: window,
+		})
+	}
+}
+
+impl Drop for PowerNotifications {
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	fn drop(&mut self) {
+		#[cfg(not(windows))]
+		return;
+		unsafe { UnregisterSuspendResumeNotification(self.handle) };
+	}
+}
@@ -0,0 +1,79 @@
+use std::{io, mem};
+
+use windows::Win32::{
+	Foundation::{LPARAM, WPARAM},
+	UI::WindowsAndMessaging::PostThreadMessageW,
+};
+
+use crate::{Hotkey, HotkeyId, WM_UNREGISTER_HOTKEY};
+
+/// A registered [`Hotkey`] that's unregistered when dropped, see [`Hotkey::register_owned`].
+///
+/// Hotkeys belong to the thread that registers them, which is the only one that can unregister them, and receives their
+/// presses. A registration can still move to and drop on another thread: it then posts a message to its thread, whose
+/// message loop (e.g. [`await_event`](crate::await_event)) unregisters the hotkey when it takes the message. So the
+/// hotkey stays registered until then, and for good if the thread doesn't run a message loop anymore.
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Registration {
+	id:     HotkeyId,
+	hotkey: Hotkey,
+	/// The thread that registered the hotkey.
+	thread: u32,
+}
+
+impl Registration {
+	/// Takes ownership of the hotkey the calling thread registered under the identifier.
+	pub(crate) fn new(id: HotkeyId, hotkey: Hotkey) -> Self {
+		Self {
+			id,
+			hotkey,
+			thread: crate::current_thread_id(),
+		}
+	}
+
+	/// The identifier the hotkey is registered under.
+	pub fn id(&self) -> HotkeyId { self.id }
+
+	/// The registered hotkey.
+	pub fn hotkey(&self) -> Hotkey { self.hotkey }
+
+	/// The Windows identifier of the thread that registered the hotkey (see [`current_thread_id`](crate::current_thread_id)).
+	pub fn thread_id(&self) -> u32 { self.thread }
+
+	/// Unregisters the hotkey, like dropping the registration but with its result.
+	///
+	/// On another thread than the registering one, it succeeds once the message to unregister is posted.
+	pub fn unregister(self) -> io::Result<()> {
+		let result = self.release();
+		mem::forget(self);
+		result
+	}
+
+	/// Unregisters the hotkey on its thread, or has its thread's message loop unregister it.
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	fn release(&self) -> io::Result<()> {
+		if crate::current_thread_id() == self.thread {
+			return self.hotkey.unregister_with_id(self.id);
+		}
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let success = unsafe {
+			PostThreadMessageW(
+				self.thread,
+				WM_UNREGISTER_HOTKEY,
+				WPARAM(self.id.0 as _),
+				LPARAM(0),
+			)
+		}
+		.as_bool();
+		if success {
+			Ok(())
+		} else {
+			Err(io::Error::last_os_error())
+		}
+	}
+}
+
+impl Drop for Registration {
+	fn drop(&mut self) { let _ = self.release(); }
+}
@@ -0,0 +1,136 @@
+use std::{io, mem, thread, time::Duration};
+
+use windows::{
+	core::PCWSTR,
+	Win32::{
+		Foundation::{
+			ERROR_BUSY, ERROR_FILE_NOT_FOUND, ERROR_LOCK_VIOLATION, ERROR_SHARING_VIOLATION,
+			ERROR_SUCCESS, WIN32_ERROR,
+		},
+		System::Registry::{
+			RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW, HKEY, REG_DWORD, REG_SZ,
+			RRF_RT_REG_DWORD,
+		},
+	},
+};
+
+/// Encodes the string as a null-terminated wide string, e.g. for a value name.
+pub(crate) fn wide(s: &str) -> Vec<u16> { s.encode_utf16().chain([0]).collect() }
+
+/// Converts the result of a registry function.
+fn check(result: WIN32_ERROR) -> io::Result<()> {
+	if result == ERROR_SUCCESS {
+		Ok(())
+	} else {
+		Err(io::Error::from_raw_os_error(result.0 as _))
+	}
+}
+
+/// The errors of a registry write that another process holding the hive or key causes, e.g. right after boot, so the
+/// write may succeed when retried.
+const TRANSIENT_ERRORS: [WIN32_ERROR; 3] =
+	[ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION, ERROR_BUSY];
+/// How many times to retry a registry write that fails with a transient error.
+const RETRIES: u32 = 4;
+/// The wait before the first retry, which doubles with each retry after.
+const RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Performs the registry write, retrying it up to [`RETRIES`] times while it fails with a transient error, waiting
+/// [`RETRY_DELAY`] before the first retry and double the previous wait before each one after.
+///
+/// Other errors fail immediately.
+pub(crate) fn retry_transient(mut write: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+	let mut delay = RETRY_DELAY;
+	for _ in 0..RETRIES {
+		match write() {
+			Err(e)
+				if TRANSIENT_ERRORS
+					.iter()
+					.any(|code| e.raw_os_error() == Some(code.0 as _)) =>
+			{
+				thread::sleep(delay);
+				delay *= 2;
+			}
+			result => return result,
+		}
+	}
+	write()
+}
+
+/// Reads a `REG_DWORD` value, [`None`] if it or its key is absent.
+///
+/// Corresponds to [RegGetValueW](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-reggetvaluew).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub(crate) fn get_dword(root: HKEY, key: PCWSTR, value: PCWSTR) -> io::Result<Option<u32>> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let mut data: u32 = 0;
+	let mut size = mem::size_of_val(&data) as u32;
+	let result = unsafe {
+		RegGetValueW(
+			root,
+			key,
+			value,
+			RRF_RT_REG_DWORD,
+			None,
+			Some(&mut data as *mut _ as *mut _),
+			Some(&mut size),
+		)
+	};
+	match result {
+		ERROR_FILE_NOT_FOUND => Ok(None),
+		result => check(result).map(|()| Some(data)),
+	}
+}
+
+/// Writes a `REG_DWORD` value, creating its key if needed.
+///
+/// Corresponds to [RegSetKeyValueW](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regsetkeyvaluew).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub(crate) fn set_dword(root: HKEY, key: PCWSTR, value: PCWSTR, data: u32) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	check(unsafe {
+		RegSetKeyValueW(
+			root,
+			key,
+			value,
+			REG_DWORD.0,
+			Some(&data as *const _ as *const _),
+			mem::size_of_val(&data) as _,
+		)
+	})
+}
+
+/// Writes a `REG_SZ` value, creating its key if needed.
+///
+/// Corresponds to [RegSetKeyValueW](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regsetkeyvaluew).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub(crate) fn set_string(root: HKEY, key: PCWSTR, value: PCWSTR, data: &str) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let data = wide(data);
+	check(unsafe {
+		RegSetKeyValueW(
+			root,
+			key,
+			value,
+			REG_SZ.0,
+			Some(data.as_ptr() as *const _),
+			mem::size_of_val(data.as_slice()) as _,
+		)
+	})
+}
+
+/// Deletes a value, succeeding if it's already absent.
+///
+/// Corresponds to [RegDeleteKeyValueW](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regdeletekeyvaluew).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+pub(crate) fn delete_value(root: HKEY, key: PCWSTR, value: PCWSTR) -> io::Result<()> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	match unsafe { RegDeleteKeyValueW(root, key, value) } {
+		ERROR_FILE_NOT_FOUND => Ok(()),
+		result => check(result),
+	}
+}
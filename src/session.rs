@@ -0,0 +1,148 @@
+use std::{io, mem, time::Duration};
+
+use windows::{
+	core::PWSTR,
+	Win32::{
+		System::{
+			RemoteDesktop::{
+				WTSFreeMemory, WTSQuerySessionInformationW, WTSRegisterSessionNotification,
+				WTSSessionInfoEx, WTSUnRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+				WTSINFOEXW, WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION, WTS_SESSIONSTATE_LOCK,
+				WTS_SESSIONSTATE_UNKNOWN, WTS_SESSIONSTATE_UNLOCK,
+			},
+			SystemInformation::{GetTickCount, GetVersionExW, OSVERSIONINFOW},
+		},
+		UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+	},
+};
+
+use crate::window::MessageWindow;
+
+/// A change in the session's state.
+#[derive(Debug, Hash, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum SessionEvent {
+	/// The session was locked, by any means.
+	Lock,
+	/// The session was unlocked.
+	Unlock,
+}
+
+/// A subscription to the session's lock and unlock notifications, received by the message loop as
+/// [`HotkeyEvent::Session`](crate::HotkeyEvent::Session) events, until dropped.
+///
+/// Like hotkeys, the notifications are delivered to the thread that subscribes, which should be the one that pumps the messages.
+///
+/// Corresponds to [WTSRegisterSessionNotification](https://learn.microsoft.com/en-us/windows/win32/api/wtsapi32/nf-wtsapi32-wtsregistersessionnotification).
+#[derive(Debug)]
+pub struct SessionNotifications {
+	window: MessageWindow,
+}
+
+impl SessionNotifications {
+	/// Subscribes the calling thread to the current session's notifications.
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub fn register() -> io::Result<Self> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let window = MessageWindow::new()?;
+		let success =
+			unsafe { WTSRegisterSessionNotification(window.handle(), NOTIFY_FOR_THIS_SESSION) }
+				.as_bool();
+		if success {
+			Ok(Self { window })
+		} else {
+			Err(io::Error::last_os_error())
+		}
+	}
+}
+
+impl Drop for SessionNotifications {
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	fn drop(&mut self) {
+		#[cfg(not(windows))]
+		return;
+		unsafe { WTSUnRegisterSessionNotification(self.window.handle()) };
+	}
+}
+
+/// Gets whether the current session is locked.
+///
+/// Windows 7 and Server 2008 R2 report the lock state inverted, which this accounts for. Older systems don't report it,
+/// so it fails with [`io::ErrorKind::Unsupported`] on them.
+///
+/// Corresponds to [WTSQuerySessionInformationW](https://learn.microsoft.com/en-us/windows/win32/api/wtsapi32/nf-wtsapi32-wtsquerysessioninformationw)
+/// with `WTSSessionInfoEx`.
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn is_workstation_locked() -> io::Result<bool> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let mut buffer = PWSTR::null();
+	let mut size = 0;
+	let success = unsafe {
+		WTSQuerySessionInformationW(
+			WTS_CURRENT_SERVER_HANDLE,
+			WTS_CURRENT_SESSION,
+			WTSSessionInfoEx,
+			&mut buffer,
+			&mut size,
+		)
+	}
+	.as_bool();
+	if !success {
+		return Err(io::Error::last_os_error());
+	}
+	let info = unsafe { &*(buffer.0 as *const WTSINFOEXW) };
+	let flags = if info.Level == 1 {
+		Some(unsafe { info.Data.WTSInfoExLevel1.SessionFlags })
+	} else {
+		None
+	};
+	unsafe { WTSFreeMemory(buffer.0 as _) };
+	let locked_flag = if is_windows_7() {
+		WTS_SESSIONSTATE_UNLOCK
+	} else {
+		WTS_SESSIONSTATE_LOCK
+	};
+	match flags.map(|flags| flags as u32) {
+		Some(WTS_SESSIONSTATE_UNKNOWN) => Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"the system doesn't report whether the session is locked",
+		)),
+		Some(flags) => Ok(flags == locked_flag),
+		None => Err(io::Error::other("unexpected session information level")),
+	}
+}
+
+/// Whether the system is Windows 7 or Server 2008 R2, i.e. version 6.1.
+///
+/// Later versions may report 6.2 to processes without a compatibility manifest, which doesn't matter here.
+///
+/// Corresponds to [GetVersionExW](https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getversionexw).
+fn is_windows_7() -> bool {
+	let mut info = OSVERSIONINFOW {
+		dwOSVersionInfoSize: mem::size_of::<OSVERSIONINFOW>() as _,
+		..Default::default()
+	};
+	unsafe { GetVersionExW(&mut info) }.as_bool()
+		&& (info.dwMajorVersion, info.dwMinorVersion) == (6, 1)
+}
+
+/// Gets how long it's been since the user's last input to the session.
+///
+/// The system's input timestamps wrap around every 49.7 days, which is accounted for as long as the user wasn't idle for longer.
+///
+/// Corresponds to [GetLastInputInfo](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getlastinputinfo).
+#[cfg_attr(not(windows), allow(unreachable_code))]
+pub fn idle_duration() -> io::Result<Duration> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let mut info = LASTINPUTINFO {
+		cbSize: mem::size_of::<LASTINPUTINFO>() as _,
+		dwTime: 0,
+	};
+	if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+		return Err(io::Error::last_os_error());
+	}
+	let now = unsafe { GetTickCount() };
+	Ok(Duration::from_millis(now.wrapping_sub(info.dwTime).into()))
+}
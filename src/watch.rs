@@ -0,0 +1,123 @@
+use std::{
+	io,
+	sync::mpsc,
+	thread::{self, JoinHandle},
+};
+
+use windows::{
+	core::PCWSTR,
+	Win32::{
+		Foundation::{CloseHandle, ERROR_SUCCESS, HANDLE, WAIT_OBJECT_0},
+		System::{
+			Registry::{
+				RegCloseKey, RegNotifyChangeKeyValue, RegOpenKeyExW, HKEY, KEY_NOTIFY,
+				REG_NOTIFY_CHANGE_LAST_SET,
+			},
+			Threading::{CreateEventW, SetEvent, WaitForMultipleObjects, INFINITE},
+		},
+	},
+};
+
+use crate::{PolicyScope, LOCK_POLICY_KEY};
+
+/// A watch over the workstation locking policy, see [`watch_lock_policy`].
+///
+/// Dropping it stops watching.
+#[derive(Debug)]
+pub struct PolicyWatcher {
+	/// The event that stops the thread.
+	stop:   HANDLE,
+	thread: Option<JoinHandle<()>>,
+}
+
+/// Spawns a thread that calls the callback whenever the locking policy of the scope changes, e.g. by group policy or
+/// another program, until the returned watcher is dropped.
+///
+/// It's notified of changes to any value of the policy's registry key, not only `DisableLockWorkstation`, so the
+/// callback should read the policy again (e.g. with [`get_lock_enabled_in`](crate::get_lock_enabled_in)) rather than
+/// assume it changed. Changes made by the process itself are notified too.
+///
+/// It fails with [`io::ErrorKind::NotFound`] if the policy's key doesn't exist, which it doesn't until a policy is set.
+///
+/// Corresponds to [RegNotifyChangeKeyValue](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regnotifychangekeyvalue).
+#[cfg_attr(not(windows), allow(unreachable_code, unused_variables, unused_mut))]
+pub fn watch_lock_policy(
+	scope: PolicyScope,
+	mut callback: impl FnMut() + Send + 'static,
+) -> io::Result<PolicyWatcher> {
+	#[cfg(not(windows))]
+	return Err(crate::unsupported());
+	let mut key = HKEY::default();
+	let result = unsafe { RegOpenKeyExW(scope.root(), LOCK_POLICY_KEY, 0, KEY_NOTIFY, &mut key) };
+	if result != ERROR_SUCCESS {
+		return Err(io::Error::from_raw_os_error(result.0 as _));
+	}
+	let events = unsafe {
+		CreateEventW(None, false, false, PCWSTR::null()).and_then(|change| {
+			CreateEventW(None, true, false, PCWSTR::null())
+				.map(|stop| (change, stop))
+				.inspect_err(|_| {
+					CloseHandle(change);
+				})
+		})
+	};
+	let (change, stop) = match events {
+		Ok(events) => events,
+		Err(e) => {
+			unsafe { RegCloseKey(key) };
+			return Err(e.into());
+		}
+	};
+	let (setup_sender, setup) = mpsc::channel();
+	let thread = thread::spawn(move || {
+		// the notification is tied to the thread that asks for it, so it's asked for on this one
+		let arm = || match unsafe {
+			RegNotifyChangeKeyValue(key, false, REG_NOTIFY_CHANGE_LAST_SET, change, true)
+		} {
+			ERROR_SUCCESS => Ok(()),
+			error => Err(io::Error::from_raw_os_error(error.0 as _)),
+		};
+		let armed = arm();
+		let failed = armed.is_err();
+		let _ = setup_sender.send(armed);
+		if !failed {
+			while unsafe { WaitForMultipleObjects(&[change, stop], false, INFINITE) }
+				== WAIT_OBJECT_0
+			{
+				// rearmed before the callback so changes it makes or races with are notified
+				let rearmed = arm();
+				callback();
+				if rearmed.is_err() {
+					break;
+				}
+			}
+		}
+		unsafe {
+			RegCloseKey(key);
+			CloseHandle(change);
+		}
+	});
+	let watcher = PolicyWatcher {
+		stop,
+		thread: Some(thread),
+	};
+	match setup.recv() {
+		Ok(result) => result.map(|()| watcher),
+		Err(_) => Err(io::Error::other(
+			"the watcher thread exited before watching",
+		)),
+	}
+}
+
+impl Drop for PolicyWatcher {
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	fn drop(&mut self) {
+		#[cfg(not(windows))]
+		return;
+		unsafe { SetEvent(self.stop) };
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+		unsafe { CloseHandle(self.stop) };
+	}
+}
@@ -0,0 +1,232 @@
+use std::{cell::RefCell, fmt, io, marker::PhantomData};
+
+use windows::{
+	core::PCWSTR,
+	w,
+	Win32::{
+		Foundation::{ERROR_CLASS_ALREADY_EXISTS, HWND, LPARAM, LRESULT, WPARAM},
+		System::{LibraryLoader::GetModuleHandleW, Threading::GetCurrentThreadId},
+		UI::{
+			Input::KeyboardAndMouse::{RegisterHotKey, UnregisterHotKey},
+			WindowsAndMessaging::{
+				CreateWindowExW, DefWindowProcW, DestroyWindow, PostThreadMessageW, RegisterClassW,
+				HWND_MESSAGE, WINDOW_EX_STYLE, WINDOW_STYLE, WM_HOTKEY, WM_POWERBROADCAST,
+				WM_WTSSESSION_CHANGE, WNDCLASSW,
+			},
+		},
+	},
+};
+
+use crate::{Hotkey, HotkeyEvent, HotkeyId, Message, RegisterError};
+
+/// The window class of [`MessageWindow`]s.
+const CLASS_NAME: PCWSTR = w!("winlock");
+
+/// Messages the window reposts to the thread's message queue (see [`MessageWindow`]).
+const FORWARDED_MESSAGES: &[u32] = &[WM_POWERBROADCAST, WM_WTSSESSION_CHANGE, WM_HOTKEY];
+
+/// A callback for the events of a window (see [`HotkeyWindow::with_callback`]).
+type Callback = Box<dyn FnMut(HotkeyEvent)>;
+
+thread_local! {
+	/// The callbacks of the thread's windows, which receive their forwarded messages instead of the message queue.
+	static CALLBACKS: RefCell<Vec<(isize, Callback)>> = RefCell::default();
+}
+
+/// A [message-only window](https://learn.microsoft.com/en-us/windows/win32/winmsg/window-features#message-only-windows),
+/// for APIs that notify a window rather than a thread.
+///
+/// The [`FORWARDED_MESSAGES`] sent to it are reposted to the thread's message queue, so the message loop receives them without
+/// dispatching, unless the window has a callback, which receives them instead. It's destroyed when dropped, and like any window it belongs to the thread that created it.
+#[derive(Debug)]
+pub(crate) struct MessageWindow(HWND);
+
+impl MessageWindow {
+	/// Creates a message-only window for the calling thread.
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	pub(crate) fn new() -> io::Result<Self> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let instance = unsafe { GetModuleHandleW(None) }?;
+		let class = WNDCLASSW {
+			lpfnWndProc: Some(window_procedure),
+			hInstance: instance,
+			lpszClassName: CLASS_NAME,
+			..Default::default()
+		};
+		if unsafe { RegisterClassW(&class) } == 0 {
+			let error = io::Error::last_os_error();
+			if error.raw_os_error() != Some(ERROR_CLASS_ALREADY_EXISTS.0 as _) {
+				return Err(error);
+			}
+		}
+		let window = unsafe {
+			CreateWindowExW(
+				WINDOW_EX_STYLE::default(),
+				CLASS_NAME,
+				PCWSTR::null(),
+				WINDOW_STYLE::default(),
+				0,
+				0,
+				0,
+				0,
+				HWND_MESSAGE,
+				None,
+				instance,
+				None,
+			)
+		};
+		if window.0 == 0 {
+			Err(io::Error::last_os_error())
+		} else {
+			Ok(Self(window))
+		}
+	}
+
+	/// The window's handle.
+	pub(crate) fn handle(&self) -> HWND { self.0 }
+}
+
+impl Drop for MessageWindow {
+	#[cfg_attr(not(windows), allow(unreachable_code))]
+	fn drop(&mut self) {
+		#[cfg(not(windows))]
+		return;
+		unsafe { DestroyWindow(self.0) };
+		CALLBACKS.with(|callbacks| {
+			callbacks
+				.borrow_mut()
+				.retain(|&(window, _)| window != self.0 .0)
+		});
+	}
+}
+
+/// A [message-only window](https://learn.microsoft.com/en-us/windows/win32/winmsg/window-features#message-only-windows)
+/// that hotkeys can be registered to, instead of to the thread.
+///
+/// Hotkeys registered to the thread are posted to its message queue, which fails to deliver them when something other
+/// than winlock runs the thread's message loop, e.g. a GUI framework. A window's messages are dispatched to it by any
+/// message loop, and it hands them to its [callback](HotkeyWindow::with_callback) or, lacking one, reposts them to the
+/// thread's message queue for [`await_event`](crate::await_event).
+///
+/// Like any window it belongs to the thread that created it, which must be the one that runs the message loop, so it
+/// isn't [`Send`]. Its hotkeys are unregistered when it's dropped.
+///
+/// [`Hotkey::register`] keeps registering to the thread rather than to a window, since a window would have to outlive the
+/// registration and be kept by the caller; register to a window with [`register`](HotkeyWindow::register) instead.
+pub struct HotkeyWindow {
+	window:  MessageWindow,
+	/// Keeps the window on its thread.
+	_thread: PhantomData<*const ()>,
+}
+
+impl HotkeyWindow {
+	/// Creates a window for the calling thread, whose events go to the thread's message queue.
+	pub fn new() -> io::Result<Self> {
+		Ok(Self {
+			window:  MessageWindow::new()?,
+			_thread: PhantomData,
+		})
+	}
+
+	/// Creates a window for the calling thread, whose events go to the callback.
+	///
+	/// The callback runs in the message loop when the window's messages are dispatched, and mustn't drop the window.
+	pub fn with_callback(callback: impl FnMut(HotkeyEvent) + 'static) -> io::Result<Self> {
+		let window = Self::new()?;
+		CALLBACKS.with(|callbacks| {
+			callbacks
+				.borrow_mut()
+				.push((window.handle().0, Box::new(callback)))
+		});
+		Ok(window)
+	}
+
+	/// The window's handle, e.g. to tell which messages of the message loop are the window's.
+	pub fn handle(&self) -> HWND { self.window.handle() }
+
+	/// Registers the [`Hotkey`] to the window under the given identifier.
+	///
+	/// Identifiers must be unique per window.
+	///
+	/// See [`Hotkey::register_with_id`].
+	#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+	pub fn register(&self, id: HotkeyId, hotkey: Hotkey) -> Result<(), RegisterError> {
+		#[cfg(not(windows))]
+		return Err(RegisterError::Os(crate::unsupported()));
+		hotkey.check_modifiers()?;
+		let success = unsafe {
+			RegisterHotKey(
+				self.handle(),
+				id.0,
+				hotkey.modifiers.into(),
+				hotkey.key_code.0,
+			)
+		}
+		.as_bool();
+		if success {
+			Ok(())
+		} else {
+			Err(RegisterError::from(io::Error::last_os_error()))
+		}
+	}
+
+	/// Unregisters the hotkey registered to the window under the given identifier.
+	#[cfg_attr(not(windows), allow(unreachable_code, unused_variables))]
+	pub fn unregister(&self, id: HotkeyId) -> io::Result<()> {
+		#[cfg(not(windows))]
+		return Err(crate::unsupported());
+		let success = unsafe { UnregisterHotKey(self.handle(), id.0) }.as_bool();
+		if success {
+			Ok(())
+		} else {
+			Err(io::Error::last_os_error())
+		}
+	}
+}
+
+impl fmt::Debug for HotkeyWindow {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("HotkeyWindow")
+			.field(&self.handle().0)
+			.finish()
+	}
+}
+
+/// Calls the window's callback with the event, returning whether it has one.
+fn call_back(window: HWND, event: HotkeyEvent) -> bool {
+	// the callback is taken out while it runs, so it may create windows
+	let Some(mut callback) = CALLBACKS.with(|callbacks| {
+		let mut callbacks = callbacks.borrow_mut();
+		let index = callbacks
+			.iter()
+			.position(|&(handle, _)| handle == window.0)?;
+		Some(callbacks.swap_remove(index).1)
+	}) else {
+		return false;
+	};
+	callback(event);
+	CALLBACKS.with(|callbacks| callbacks.borrow_mut().push((window.0, callback)));
+	true
+}
+
+unsafe extern "system" fn window_procedure(
+	window: HWND,
+	message: u32,
+	wparam: WPARAM,
+	lparam: LPARAM,
+) -> LRESULT {
+	if FORWARDED_MESSAGES.contains(&message) {
+		let event = HotkeyEvent::from_message(Message {
+			message,
+			wparam: wparam.0,
+			lparam: lparam.0,
+		});
+		if !call_back(window, event) {
+			PostThreadMessageW(GetCurrentThreadId(), message, wparam, lparam);
+		}
+		LRESULT(0)
+	} else {
+		DefWindowProcW(window, message, wparam, lparam)
+	}
+}
@@ -0,0 +1,36 @@
+use winlock::Action;
+
+fn command_line(command: &str) -> Vec<String> {
+	Action::Exec(command.to_owned()).command_line().unwrap()
+}
+
+#[test]
+fn splits_by_whitespace() {
+	assert_eq!(command_line("notepad a.txt"), ["notepad", "a.txt"]);
+	assert_eq!(command_line("  notepad \t a.txt  "), ["notepad", "a.txt"]);
+}
+
+#[test]
+fn keeps_quoted_whitespace() {
+	assert_eq!(
+		command_line(r#""C:\Program Files\app.exe" "to do.txt""#),
+		[r"C:\Program Files\app.exe", "to do.txt"]
+	);
+	assert_eq!(command_line(r#"echo """#), ["echo", ""]);
+}
+
+#[test]
+fn joins_embedded_quotes() {
+	assert_eq!(
+		command_line(r#"app --name="a b"c d"#),
+		["app", "--name=a bc", "d"]
+	);
+}
+
+#[test]
+fn splits_empty_commands() {
+	assert!(command_line("").is_empty());
+	assert!(command_line(" \t ").is_empty());
+	assert_eq!(Action::exec(" "), None);
+	assert_eq!(Action::Lock.command_line(), None);
+}
@@ -0,0 +1,69 @@
+use std::{
+	fs,
+	sync::atomic::{AtomicUsize, Ordering},
+	time::{Duration, UNIX_EPOCH},
+};
+
+use winlock::AuditLogger;
+
+/// The entries of the events logged at the times since the epoch, as written to a log file.
+fn log(entries: &[(i64, &str)]) -> Vec<String> {
+	static NEXT: AtomicUsize = AtomicUsize::new(0);
+	let path = std::env::temp_dir().join(format!(
+		"winlock-audit-{}-{}.log",
+		std::process::id(),
+		NEXT.fetch_add(1, Ordering::Relaxed)
+	));
+	let _ = fs::remove_file(&path);
+	let mut logger = AuditLogger::open(&path).unwrap();
+	for &(seconds, event) in entries {
+		let time = if seconds < 0 {
+			UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+		} else {
+			UNIX_EPOCH + Duration::from_secs(seconds as u64)
+		};
+		logger.log_at(time, event).unwrap();
+	}
+	drop(logger);
+	let log = fs::read_to_string(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+	log.lines().map(str::to_owned).collect()
+}
+
+#[test]
+fn timestamps_civil_dates() {
+	assert_eq!(
+		log(&[
+			(0, "epoch"),
+			(951_782_400, "leap day of a century divisible by 400"),
+			(1_709_164_799, "last second before a leap day"),
+			(1_709_164_800, "leap day"),
+			(1_709_251_200, "after the leap day"),
+			(4_107_456_000, "century not divisible by 400"),
+			(4_107_542_400, "the day after"),
+			(-1, "before the epoch"),
+		]),
+		[
+			"1970-01-01T00:00:00Z epoch",
+			"2000-02-29T00:00:00Z leap day of a century divisible by 400",
+			"2024-02-28T23:59:59Z last second before a leap day",
+			"2024-02-29T00:00:00Z leap day",
+			"2024-03-01T00:00:00Z after the leap day",
+			"2100-02-28T00:00:00Z century not divisible by 400",
+			"2100-03-01T00:00:00Z the day after",
+			"1970-01-01T00:00:00Z before the epoch",
+		]
+	);
+}
+
+#[test]
+fn appends() {
+	let entries = log(&[(1_686_734_813, "locked"), (1_686_734_873, "unlocked")]);
+	assert_eq!(
+		entries,
+		[
+			"2023-06-14T09:26:53Z locked",
+			"2023-06-14T09:27:53Z unlocked"
+		]
+	);
+}
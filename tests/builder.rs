@@ -0,0 +1,75 @@
+use winlock::{keys, BuildHotkeyError, Hotkey, Modifiers};
+
+#[test]
+fn builds_modifier_combinations() {
+	assert_eq!(
+		Hotkey::builder().ctrl().alt().key(keys::L).build(),
+		Ok(Hotkey::new(Modifiers::Control | Modifiers::Alt, keys::L))
+	);
+	assert_eq!(
+		Hotkey::builder()
+			.shift()
+			.win()
+			.no_repeat()
+			.modifier(Modifiers::Control)
+			.key(keys::F5)
+			.build(),
+		Ok(Hotkey::new(Modifiers::all() - Modifiers::Alt, keys::F5))
+	);
+	assert_eq!(
+		Hotkey::builder().key(keys::F13).build(),
+		Ok(Hotkey::new(Modifiers::empty(), keys::F13))
+	);
+}
+
+#[test]
+fn builds_on_hotkey() {
+	let hotkey = Hotkey::new(Modifiers::Control, keys::J);
+	assert_eq!(
+		Hotkey::builder().win().hotkey(hotkey).build(),
+		Ok(Hotkey::new(Modifiers::Control | Modifiers::Win, keys::J))
+	);
+}
+
+#[test]
+fn last_key_wins() {
+	assert_eq!(
+		Hotkey::builder()
+			.ctrl()
+			.char('\u{E000}')
+			.key(keys::K)
+			.build(),
+		Ok(Hotkey::new(Modifiers::Control, keys::K))
+	);
+}
+
+#[test]
+fn fails_without_key() {
+	assert_eq!(
+		Hotkey::builder().ctrl().alt().build(),
+		Err(BuildHotkeyError::MissingKey)
+	);
+}
+
+// the stub maps no characters, so it'd pass trivially on other targets
+#[cfg(windows)]
+#[test]
+fn fails_on_unmappable_char() {
+	// a private use character, which no layout emits
+	assert_eq!(
+		Hotkey::builder()
+			.ctrl()
+			.char('\u{E000}')
+			.build_on(winlock::KeyboardLayout::current()),
+		Err(BuildHotkeyError::UnmappableChar('\u{E000}'))
+	);
+}
+
+#[cfg(windows)]
+#[test]
+fn fails_on_astral_char() {
+	assert_eq!(
+		Hotkey::builder().ctrl().char('😀').build(),
+		Err(BuildHotkeyError::UnmappableChar('😀'))
+	);
+}
@@ -0,0 +1,55 @@
+// runs the binary, which needs the `cli` feature
+#![cfg(feature = "cli")]
+
+use std::process::Command;
+
+/// The arguments of the command the binary would install to run at login, given the arguments.
+fn autostart_args(args: &[&str]) -> Vec<String> {
+	let exe = env!("CARGO_BIN_EXE_winlock");
+	let output = Command::new(exe)
+		.args(args)
+		.args(["--install-autostart", "--dry-run"])
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "{output:?}");
+	let command = String::from_utf8(output.stdout).unwrap();
+	// the executable comes first, quoted if its path has spaces
+	let args = command.trim_end();
+	let args = args
+		.strip_prefix(&format!("\"{exe}\""))
+		.or_else(|| args.strip_prefix(exe))
+		.unwrap();
+	args.split_whitespace().map(str::to_owned).collect()
+}
+
+#[test]
+fn spells_out_runtime_flags() {
+	assert_eq!(
+		autostart_args(&["-dcwk", "j", "--machine", "--grace=5"]),
+		[
+			"--disable-windows",
+			"--machine",
+			"--grace=5",
+			"--key=j",
+			"--ctrl",
+			"--windows"
+		]
+	);
+}
+
+#[test]
+fn leaves_out_one_shot_flags() {
+	assert_eq!(
+		autostart_args(&["--print-resolved", "-d"]),
+		["--disable-windows"]
+	);
+	assert!(autostart_args(&[]).is_empty());
+}
+
+#[test]
+fn keeps_repeated_values() {
+	assert_eq!(
+		autostart_args(&["--bind", "Ctrl+Alt+S=sleep", "--bind=Ctrl+F5=lock"]),
+		["--bind=Ctrl+Alt+S=sleep", "--bind=Ctrl+F5=lock"]
+	);
+}
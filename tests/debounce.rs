@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+use winlock::Debounce;
+
+const COOLDOWN: Duration = Duration::from_millis(500);
+
+#[test]
+fn lets_the_first_event_through() {
+	let mut debounce = Debounce::new(COOLDOWN);
+	assert!(debounce.ready_at(Instant::now()));
+}
+
+#[test]
+fn holds_events_until_the_cooldown_ends() {
+	let start = Instant::now();
+	let mut debounce = Debounce::new(COOLDOWN);
+	assert!(debounce.ready_at(start));
+	assert!(!debounce.ready_at(start));
+	assert!(!debounce.ready_at(start + COOLDOWN - Duration::from_nanos(1)));
+	assert!(debounce.ready_at(start + COOLDOWN));
+	// the cooldown restarts from the event let through
+	assert!(!debounce.ready_at(start + COOLDOWN + COOLDOWN / 2));
+	assert!(debounce.ready_at(start + COOLDOWN * 2));
+}
+
+#[test]
+fn held_events_dont_extend_the_cooldown() {
+	let start = Instant::now();
+	let mut debounce = Debounce::new(COOLDOWN);
+	assert!(debounce.ready_at(start));
+	for elapsed in [100, 200, 300, 400, 499] {
+		assert!(!debounce.ready_at(start + Duration::from_millis(elapsed)));
+	}
+	assert!(debounce.ready_at(start + COOLDOWN));
+}
+
+#[test]
+fn lets_everything_through_without_a_cooldown() {
+	let now = Instant::now();
+	let mut debounce = Debounce::new(Duration::ZERO);
+	assert!(debounce.ready_at(now));
+	assert!(debounce.ready_at(now));
+}
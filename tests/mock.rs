@@ -0,0 +1,180 @@
+#![cfg(feature = "mock")]
+
+use std::panic::AssertUnwindSafe;
+
+use windows::Win32::UI::WindowsAndMessaging::{
+	WM_APP, WM_HOTKEY, WM_POWERBROADCAST, WM_QUIT, WM_USER, WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK,
+};
+use winlock::{
+	keys, Hotkey, HotkeyEvent, HotkeyId, HotkeyManager, LockPolicySnapshot, ManagerEvent, Message,
+	MockApi, Modifiers, SessionEvent, WinApi,
+};
+
+const LOCK: Hotkey = Hotkey::new(Modifiers::Control.union(Modifiers::Alt), keys::L);
+
+fn hotkey_message(id: HotkeyId) -> Message {
+	Message {
+		message: WM_HOTKEY,
+		wparam:  id.0 as _,
+		lparam:  0,
+	}
+}
+
+#[test]
+fn manager_dispatches_hotkeys() {
+	let mut manager = HotkeyManager::with_api(MockApi::new());
+	let id = manager.register(LOCK).unwrap();
+	manager.api().push_message(hotkey_message(id));
+	manager
+		.api()
+		.push_message(hotkey_message(HotkeyId(id.0 + 1)));
+	assert_eq!(
+		manager.await_event().unwrap(),
+		ManagerEvent::Hotkey { id, hotkey: LOCK }
+	);
+	assert_eq!(manager.await_event().unwrap(), ManagerEvent::Other);
+	assert_eq!(manager.await_event().unwrap(), ManagerEvent::Quit);
+}
+
+#[test]
+fn manager_dispatches_session_changes() {
+	let manager = HotkeyManager::with_api(MockApi::new());
+	manager.api().push_message(Message {
+		message: WM_WTSSESSION_CHANGE,
+		wparam:  WTS_SESSION_LOCK as _,
+		lparam:  0,
+	});
+	assert_eq!(
+		manager.await_event().unwrap(),
+		ManagerEvent::Session(SessionEvent::Lock)
+	);
+}
+
+#[test]
+fn manager_unregisters() {
+	let mut manager = HotkeyManager::with_api(MockApi::new());
+	let id = manager.register(LOCK).unwrap();
+	assert_eq!(manager.api().hotkeys(), [(id, LOCK)]);
+	manager.unregister(id).unwrap();
+	assert!(manager.api().hotkeys().is_empty());
+	assert!(manager.unregister(id).is_err());
+}
+
+#[test]
+fn lock_policy() {
+	let api = MockApi::new();
+	assert!(winlock::get_lock_enabled_with(&api).unwrap());
+	winlock::set_lock_enabled_with(&api, false).unwrap();
+	assert_eq!(api.registry(), Some(1));
+	assert!(!winlock::get_lock_enabled_with(&api).unwrap());
+}
+
+#[test]
+fn availability() {
+	let api = MockApi::new();
+	assert!(LOCK.is_available_with_api(&api).unwrap());
+	assert!(api.hotkeys().is_empty());
+	LOCK.register_with_api(HotkeyId(1), &api).unwrap();
+	assert!(!LOCK.is_available_with_api(&api).unwrap());
+}
+
+#[test]
+fn registers_first_available_id() {
+	let api = MockApi::new();
+	let other = Hotkey::new(Modifiers::Control, keys::K);
+	other.register_with_api(HotkeyId(1), &api).unwrap();
+	let ids = (1..=3).map(HotkeyId);
+	assert_eq!(
+		LOCK.register_first_available_with_api(ids, &api).unwrap(),
+		HotkeyId(2)
+	);
+	assert!(LOCK
+		.register_first_available_with_api([HotkeyId(3)], &api)
+		.is_err());
+}
+
+#[test]
+fn only_quit_messages_quit() {
+	let api = MockApi::new();
+	for (message, wparam) in [(WM_USER, 0), (WM_POWERBROADCAST, 0xFFFF), (WM_QUIT, 0)] {
+		api.push_message(Message {
+			message,
+			wparam,
+			lparam: 0,
+		});
+	}
+	assert_eq!(winlock::await_event_with(&api).unwrap(), HotkeyEvent::Other);
+	assert_eq!(winlock::await_event_with(&api).unwrap(), HotkeyEvent::Other);
+	assert_eq!(winlock::await_event_with(&api).unwrap(), HotkeyEvent::Quit);
+}
+
+#[test]
+fn filters_other_hotkeys() {
+	let api = MockApi::new();
+	api.push_message(hotkey_message(HotkeyId(7)));
+	api.push_message(hotkey_message(Hotkey::ID));
+	let event = || {
+		winlock::await_event_with(&api)
+			.unwrap()
+			.only_for(Hotkey::ID)
+	};
+	assert_eq!(event(), HotkeyEvent::Other);
+	assert_eq!(event(), HotkeyEvent::Hotkey { id: Hotkey::ID });
+	assert_eq!(event(), HotkeyEvent::Quit);
+}
+
+#[test]
+fn unregisters_dropped_registrations() {
+	let api = MockApi::new();
+	LOCK.register_with_api(HotkeyId(3), &api).unwrap();
+	// what a registration dropped on another thread posts
+	let unregister = Message {
+		message: WM_APP + 0x3172,
+		wparam:  3,
+		lparam:  0,
+	};
+	assert_eq!(HotkeyEvent::from_message(unregister), HotkeyEvent::Other);
+	assert_eq!(api.hotkeys(), [(HotkeyId(3), LOCK)]);
+	api.push_message(unregister);
+	assert_eq!(winlock::await_event_with(&api).unwrap(), HotkeyEvent::Other);
+	assert!(api.hotkeys().is_empty());
+}
+
+#[test]
+fn restores_lock_policy_on_panic() {
+	for original in [None, Some(0), Some(1)] {
+		let api = MockApi::new();
+		api.reg_set_value(original).unwrap();
+		let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+			let _guard = winlock::disable_lock_scoped_with(&api).unwrap();
+			assert_eq!(api.registry(), Some(1));
+			panic!("forced");
+		}));
+		assert!(result.is_err());
+		assert_eq!(api.registry(), original);
+	}
+}
+
+#[test]
+fn restores_lock_policy_snapshot() {
+	let api = MockApi::new();
+	let snapshot = LockPolicySnapshot::take_with(&api).unwrap();
+	assert!(snapshot.lock_enabled());
+	winlock::set_lock_enabled_with(&api, false).unwrap();
+	snapshot.restore_with(&api).unwrap();
+	assert_eq!(api.registry(), None);
+}
+
+#[test]
+fn filters_hotkeys_among_ids() {
+	let ids = [HotkeyId(1), HotkeyId(2)];
+	for (id, expected) in [
+		(1, HotkeyEvent::Hotkey { id: HotkeyId(1) }),
+		(2, HotkeyEvent::Hotkey { id: HotkeyId(2) }),
+		(3, HotkeyEvent::Other),
+	] {
+		let event = HotkeyEvent::from_message(hotkey_message(HotkeyId(id)));
+		assert_eq!(event.only_among(&ids), expected);
+	}
+	assert_eq!(HotkeyEvent::Quit.only_among(&[]), HotkeyEvent::Quit);
+}
@@ -0,0 +1,128 @@
+use winlock::{keys, Hotkey, HotkeyOptions, Key, Modifiers, OptionsKeyError};
+
+#[test]
+fn maps_modifier_flags() {
+	assert_eq!(
+		Modifiers::from(HotkeyOptions::default()),
+		Modifiers::NoRepeat
+	);
+	assert_eq!(
+		Modifiers::from(HotkeyOptions {
+			ctrl: true,
+			alt: true,
+			..Default::default()
+		}),
+		Modifiers::Control | Modifiers::Alt | Modifiers::NoRepeat
+	);
+	assert_eq!(
+		Modifiers::from(HotkeyOptions {
+			shift: true,
+			win: true,
+			repeat: true,
+			..Default::default()
+		}),
+		Modifiers::Shift | Modifiers::Win
+	);
+}
+
+#[test]
+fn takes_virtual_code_as_is() {
+	let options = HotkeyOptions {
+		virtual_code: Some(0x4A),
+		ctrl: true,
+		win: true,
+		..Default::default()
+	};
+	assert_eq!(
+		options.into_hotkey(),
+		Ok(Some(Hotkey::new(
+			Modifiers::Control | Modifiers::Win | Modifiers::NoRepeat,
+			Key(0x4A)
+		)))
+	);
+}
+
+#[test]
+fn adds_flags_to_hotkey() {
+	let options = HotkeyOptions {
+		hotkey: Some(Hotkey::new(Modifiers::Control, keys::L)),
+		shift: true,
+		repeat: true,
+		..Default::default()
+	};
+	assert_eq!(
+		options.into_hotkey(),
+		Ok(Some(Hotkey::new(
+			Modifiers::Control | Modifiers::Shift,
+			keys::L
+		)))
+	);
+}
+
+#[test]
+fn has_no_hotkey_without_key() {
+	let options = HotkeyOptions {
+		ctrl: true,
+		..Default::default()
+	};
+	assert_eq!(options.into_hotkey(), Ok(None));
+}
+
+#[test]
+fn rejects_conflicting_keys() {
+	let hotkey = Some(Hotkey::new(Modifiers::Control, keys::L));
+	for options in [
+		HotkeyOptions {
+			virtual_code: Some(0x4A),
+			key: Some('j'),
+			..Default::default()
+		},
+		HotkeyOptions {
+			hotkey,
+			virtual_code: Some(0x4A),
+			..Default::default()
+		},
+		HotkeyOptions {
+			hotkey,
+			key: Some('j'),
+			..Default::default()
+		},
+	] {
+		assert_eq!(
+			options.into_hotkey(),
+			Err(OptionsKeyError::Conflict),
+			"{options:?}"
+		);
+	}
+}
+
+#[cfg(windows)]
+#[test]
+fn resolves_characters() {
+	let installed = winlock::KeyboardLayout::installed().unwrap();
+	let layout = winlock::KeyboardLayout::load("00000409").unwrap();
+	let options = HotkeyOptions {
+		key: Some('?'),
+		ctrl: true,
+		..Default::default()
+	};
+	assert_eq!(
+		options.into_hotkey_on(layout),
+		Ok(Some(Hotkey::new(
+			Modifiers::Control | Modifiers::Shift | Modifiers::NoRepeat,
+			// the US layout's `/` key
+			Key(0xBF)
+		)))
+	);
+	let options = HotkeyOptions {
+		key: Some('\u{E000}'),
+		..Default::default()
+	};
+	assert_eq!(
+		options.into_hotkey_on(layout),
+		Err(OptionsKeyError::MappingFail)
+	);
+	if !installed.contains(&layout) {
+		layout.unload().unwrap();
+	}
+}
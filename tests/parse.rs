@@ -0,0 +1,116 @@
+use winlock::{keys, Action, Hotkey, Key, Modifiers, ParseActionError, ParseHotkeyError};
+
+#[test]
+fn parses_modifier_aliases() {
+	for (name, modifier) in [
+		("Control", Modifiers::Control),
+		("Option", Modifiers::Alt),
+		("opt", Modifiers::Alt),
+		("Super", Modifiers::Win),
+		("META", Modifiers::Win),
+		("Cmd", Modifiers::Win),
+	] {
+		assert_eq!(Modifiers::from_names([name]), Ok(modifier), "{name}");
+	}
+	assert_eq!(
+		Modifiers::from_names(["Hyper"]),
+		Err(ParseHotkeyError::UnknownModifier("Hyper".to_owned()))
+	);
+}
+
+#[test]
+fn displays_canonical_names() {
+	let hotkey: Hotkey = "Cmd+Option+F5".parse().unwrap();
+	assert_eq!(
+		hotkey,
+		Hotkey::new(Modifiers::Win | Modifiers::Alt, keys::F5)
+	);
+	assert_eq!(hotkey.to_string(), "Alt+Win+F5");
+}
+
+#[test]
+fn parses_sided_modifier_keys() {
+	let hotkey: Hotkey = "Ctrl+RAlt".parse().unwrap();
+	assert_eq!(hotkey, Hotkey::new(Modifiers::Control, keys::RIGHT_ALT));
+	assert!(keys::RIGHT_ALT.is_extended());
+	assert!(!keys::LEFT_ALT.is_extended());
+}
+
+#[test]
+fn parses_actions() {
+	for (name, action) in [
+		("lock", Action::Lock),
+		(
+			"Force-Hibernate",
+			Action::Sleep {
+				hibernate: true,
+				force:     true,
+			},
+		),
+		("toggle-lock", Action::ToggleLockEnabled),
+		(
+			"exec:notepad \"to do.txt\"",
+			Action::Exec("notepad \"to do.txt\"".to_owned()),
+		),
+	] {
+		assert_eq!(name.parse(), Ok(action.clone()), "{name}");
+		assert!(action.to_string().eq_ignore_ascii_case(name), "{action}");
+	}
+	assert_eq!(
+		"force-lock".parse::<Action>(),
+		Err(ParseActionError::UnknownAction("force-lock".to_owned()))
+	);
+	assert_eq!(
+		"exec: ".parse::<Action>(),
+		Err(ParseActionError::EmptyCommand)
+	);
+}
+
+#[test]
+fn parses_displayed_hotkeys() {
+	for hotkey in [
+		Hotkey::new(Modifiers::Control | Modifiers::Alt, keys::L),
+		Hotkey::new(Modifiers::Win, keys::F5),
+		Hotkey::new(Modifiers::all() - Modifiers::NoRepeat, keys::ESCAPE),
+		Hotkey::new(Modifiers::Control, keys::RIGHT_ALT),
+		Hotkey::new(Modifiers::empty(), keys::F23),
+		Hotkey::new(Modifiers::Shift, Key(0xE8)),
+	] {
+		assert_eq!(hotkey.to_string().parse::<Hotkey>(), Ok(hotkey), "{hotkey}");
+	}
+}
+
+#[test]
+fn drops_no_repeat_when_displayed() {
+	let hotkey = Hotkey::new(Modifiers::Control | Modifiers::NoRepeat, keys::F5);
+	assert_eq!(hotkey.to_string(), "Ctrl+F5");
+	assert_eq!(
+		hotkey.to_string().parse::<Hotkey>(),
+		Ok(Hotkey::new(Modifiers::Control, keys::F5))
+	);
+}
+
+#[test]
+fn triggers_modifier_key_hotkeys() {
+	let left_win = Hotkey::new(Modifiers::NoRepeat, keys::LEFT_WIN);
+	// the first press, and its repeats which hold the key's own modifier down
+	assert!(left_win.is_triggered_by(keys::LEFT_WIN, Modifiers::empty()));
+	assert!(left_win.is_triggered_by(keys::LEFT_WIN, Modifiers::Win));
+	assert!(!left_win.is_triggered_by(keys::LEFT_WIN, Modifiers::Control | Modifiers::Win));
+	assert!(!left_win.is_triggered_by(keys::RIGHT_WIN, Modifiers::empty()));
+
+	let ctrl_right_alt = Hotkey::new(Modifiers::Control, keys::RIGHT_ALT);
+	assert!(ctrl_right_alt.is_triggered_by(keys::RIGHT_ALT, Modifiers::Control));
+	assert!(ctrl_right_alt.is_triggered_by(keys::RIGHT_ALT, Modifiers::Control | Modifiers::Alt));
+	assert!(!ctrl_right_alt.is_triggered_by(keys::RIGHT_ALT, Modifiers::empty()));
+	assert_eq!(keys::F5.modifier(), None);
+}
+
+#[test]
+fn triggers_exact_modifiers() {
+	let hotkey = Hotkey::new(Modifiers::Control | Modifiers::Alt, keys::L);
+	assert!(hotkey.is_triggered_by(keys::L, Modifiers::Control | Modifiers::Alt));
+	assert!(!hotkey.is_triggered_by(keys::L, Modifiers::Control));
+	assert!(!hotkey.is_triggered_by(keys::L, Modifiers::all() - Modifiers::NoRepeat));
+	assert!(!hotkey.is_triggered_by(keys::K, Modifiers::Control | Modifiers::Alt));
+}
@@ -0,0 +1,20 @@
+// actual registrations, which only Windows can make
+#![cfg(windows)]
+
+use winlock::{keys, Hotkey, HotkeyId, Modifiers, RegisterError};
+
+/// A combination no application is expected to have registered.
+const OBSCURE: Hotkey = Hotkey::new(Modifiers::all().difference(Modifiers::NoRepeat), keys::F23);
+
+#[test]
+fn registration_round_trip() {
+	let (first, second) = (HotkeyId(0x5EC0), HotkeyId(0x5EC1));
+	OBSCURE.register_with_id(first).expect("registration");
+	assert!(matches!(
+		OBSCURE.register_with_id(second),
+		Err(RegisterError::AlreadyRegistered)
+	));
+	OBSCURE.unregister_with_id(first).expect("unregistration");
+	OBSCURE.register_with_id(second).expect("re-registration");
+	OBSCURE.unregister_with_id(second).expect("unregistration");
+}